@@ -0,0 +1,560 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    io::{self, Read},
+};
+
+use crate::{
+    dates, depends_on_transitively, find_todo, format_duration, format_estimate, load_app_data, save_app_data, take_id,
+};
+use crate::filter::Filter;
+use crate::{markdown, todotxt, AppData, Page, Todo};
+
+const USAGE: &str = "usage:
+  doodoo                                   open the todo list
+  doodoo list [--filter EXPR]              todos on every page with their ids, e.g.
+                                           --filter 'tag:work and not done and due<7d'
+  doodoo done <id> [--force]               complete a todo, even if it is blocked with --force
+  doodoo import --format todotxt|markdown FILE
+                                           add todos from a file (- reads stdin), with
+                                           +projects or ## headings as pages
+  doodoo export --format todotxt|markdown [--page NAME] [--output FILE]
+                                           write every todo, to stdout by default
+  doodoo report time [--by page|todo|day] [--since DATE] [--until DATE]
+                                           tracked time totals";
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args[0].as_str() {
+        "list" => list(&args[1..]),
+        "done" => done(&args[1..]),
+        "import" => import(&args[1..]),
+        "export" => export(&args[1..]),
+        "report" => report(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command '{}'\n{}", other, USAGE).into()),
+    }
+}
+
+fn list(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let filter = match flag_value(args, "--filter")? {
+        Some(expression) => Some(Filter::parse(&expression).map_err(|err| format!("invalid filter: {}", err))?),
+        None => None,
+    };
+    let today = Local::now().date_naive();
+
+    let data = load_app_data()?;
+    for page in &data.pages {
+        let todos: Vec<&Todo> = page
+            .todos
+            .iter()
+            .filter(|todo| filter.as_ref().is_none_or(|filter| filter.matches(page, todo, today)))
+            .collect();
+        // with a filter, pages without matches are left out
+        if todos.is_empty() && filter.is_some() {
+            continue;
+        }
+        println!("{:>6}  {}", page.id, page.name);
+        for todo in todos {
+            println!("{}", format_todo(todo));
+        }
+    }
+    Ok(())
+}
+
+fn format_todo(todo: &Todo) -> String {
+    let checkbox = if todo.completed { "[x]" } else { "[ ]" };
+    format!("{:>6}  {} {}", todo.id, checkbox, todo.name)
+}
+
+fn done(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let id: u64 = match args.first().map(|id| id.parse()) {
+        Some(Ok(id)) => id,
+        _ => return Err(format!("done needs a todo id\n{}", USAGE).into()),
+    };
+    let force = args[1..].iter().any(|arg| arg == "--force");
+
+    let mut data = load_app_data()?;
+    let (page_index, todo_index) = find_todo(&data.pages, id).ok_or_else(|| format!("no todo with id {}", id))?;
+
+    let todo = &data.pages[page_index].todos[todo_index];
+    if todo.completed {
+        println!("already done: {}", todo.name);
+        return Ok(());
+    }
+    let blockers: Vec<String> = todo
+        .depends_on
+        .iter()
+        .filter_map(|id| find_todo(&data.pages, *id))
+        .map(|(page_index, todo_index)| &data.pages[page_index].todos[todo_index])
+        .filter(|dependency| !dependency.completed)
+        .map(|dependency| format!("{} '{}'", dependency.id, dependency.name))
+        .collect();
+    if !blockers.is_empty() && !force {
+        return Err(format!("'{}' is blocked by {} (use --force to complete it anyway)", todo.name, blockers.join(", ")).into());
+    }
+
+    let todos = &mut data.pages[page_index].todos;
+    let todo = &mut todos[todo_index];
+    match todo.complete_occurrence(&mut data.next_id) {
+        Some(next) => {
+            println!("done: {} (next due {})", next.name, next.due.map(|due| due.to_string()).unwrap_or_default());
+            todos.insert(todo_index + 1, next);
+        }
+        None => {
+            todo.set_completed(true);
+            println!("done: {}", todo.name);
+        }
+    }
+
+    save_app_data(&data)
+}
+
+fn import(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let format = flag_value(args, "--format")?.ok_or_else(|| format!("import needs --format\n{}", USAGE))?;
+    let path = positional(args, &["--format"]).ok_or_else(|| format!("import needs a file\n{}", USAGE))?;
+    let contents = if path == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path, err))?
+    };
+
+    // loaded first so the new todos get ids after the existing ones
+    let mut data = load_app_data()?;
+    match format.as_str() {
+        "todotxt" => {
+            let tasks: Vec<(String, Todo)> = contents
+                .lines()
+                .filter_map(todotxt::parse_line)
+                .map(|task| {
+                    let page = task.project.map_or_else(|| "inbox".to_string(), |project| page_for_project(&data, project));
+                    (page, task.todo)
+                })
+                .collect();
+            let (imported, skipped) = merge_todos(&mut data, tasks);
+            save_app_data(&data)?;
+
+            println!("imported {} todos", imported);
+            if skipped > 0 {
+                println!("skipped {} already on their page", skipped);
+            }
+        }
+        "markdown" => {
+            let (imported, updated) = merge_sections(&mut data, markdown::parse_sections(&contents, "inbox"));
+            save_app_data(&data)?;
+
+            println!("imported {} todos", imported);
+            if updated > 0 {
+                println!("updated {} already on their page", updated);
+            }
+        }
+        other => return Err(format!("unknown format '{}', expected todotxt or markdown", other).into()),
+    }
+    Ok(())
+}
+
+fn export(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let format = flag_value(args, "--format")?.ok_or_else(|| format!("export needs --format\n{}", USAGE))?;
+    let page_name = flag_value(args, "--page")?;
+    let data = load_app_data()?;
+
+    let pages: Vec<&Page> = data
+        .pages
+        .iter()
+        .filter(|page| page_name.as_ref().is_none_or(|name| page.name.eq_ignore_ascii_case(name)))
+        .collect();
+    if let (Some(name), true) = (&page_name, pages.is_empty()) {
+        return Err(format!("no page named '{}'", name).into());
+    }
+
+    let contents = match format.as_str() {
+        "todotxt" => {
+            let mut contents = String::new();
+            for page in &pages {
+                for todo in &page.todos {
+                    contents.push_str(&todotxt::format_line(&page.name, todo));
+                    contents.push('\n');
+                }
+            }
+            contents
+        }
+        "markdown" => markdown::pages_to_markdown(&pages),
+        other => return Err(format!("unknown format '{}', expected todotxt or markdown", other).into()),
+    };
+
+    match flag_value(args, "--output")? {
+        Some(path) => fs::write(&path, contents).map_err(|err| format!("can't write {}: {}", path, err))?,
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+// the existing page a `+project` was exported from, so `+work-stuff` goes back to `work stuff`
+fn page_for_project(data: &AppData, project: String) -> String {
+    data.pages
+        .iter()
+        .find(|page| todotxt::project_name(&page.name).eq_ignore_ascii_case(&project))
+        .map_or(project, |page| page.name.clone())
+}
+
+// adds todos to the pages they name, creating missing pages. todos whose name was already on
+// the page are skipped so importing the same file twice doesn't duplicate anything
+fn merge_todos(data: &mut AppData, todos: Vec<(String, Todo)>) -> (usize, usize) {
+    let existing: Vec<usize> = data.pages.iter().map(|page| page.todos.len()).collect();
+    let mut imported = 0;
+    let mut skipped = 0;
+    for (page_name, mut todo) in todos {
+        let page_index = page_index(data, page_name);
+        let page = &mut data.pages[page_index];
+        let before_import = &page.todos[..existing.get(page_index).copied().unwrap_or(0)];
+        if before_import.iter().any(|existing| existing.name == todo.name) {
+            skipped += 1;
+        } else {
+            todo.id = take_id(&mut data.next_id);
+            page.todos.push(todo);
+            imported += 1;
+        }
+    }
+    (imported, skipped)
+}
+
+// merges checklist sections into the pages they name, creating missing pages. an item already
+// on its page takes the checkbox's state instead of being added again, and nested items
+// become todos their parent depends on
+fn merge_sections(data: &mut AppData, sections: Vec<markdown::Section>) -> (usize, usize) {
+    let mut imported = 0;
+    let mut updated = 0;
+    for section in sections {
+        let page_index = page_index(data, section.page);
+        let page = &mut data.pages[page_index];
+
+        let mut ids = vec![];
+        for item in &section.items {
+            match page.todos.iter().position(|todo| todo.name == item.name) {
+                Some(position) => {
+                    let todo = &mut page.todos[position];
+                    ids.push(todo.id);
+                    if todo.notes.is_empty() {
+                        todo.notes = item.notes.clone();
+                    }
+                    if todo.completed != item.completed {
+                        // ticking a recurring todo schedules its next occurrence, as it does in the list
+                        let next = if item.completed && todo.recurrence.is_some() {
+                            todo.complete_occurrence(&mut data.next_id)
+                        } else {
+                            todo.set_completed(item.completed);
+                            None
+                        };
+                        if let Some(next) = next {
+                            page.todos.insert(position + 1, next);
+                        }
+                        updated += 1;
+                    }
+                }
+                None => {
+                    let mut todo = Todo::new(take_id(&mut data.next_id), item.name.clone());
+                    todo.set_completed(item.completed);
+                    todo.notes = item.notes.clone();
+                    ids.push(todo.id);
+                    page.todos.push(todo);
+                    imported += 1;
+                }
+            }
+        }
+
+        for (index, item) in section.items.iter().enumerate() {
+            let Some(parent) = item.parent else { continue };
+            let (parent_id, child_id) = (ids[parent], ids[index]);
+            // the same check as linking in the list, so an import can't make a todo wait on itself
+            if depends_on_transitively(&data.pages, child_id, parent_id) {
+                continue;
+            }
+            let page = &mut data.pages[page_index];
+            if let Some(todo) = page.todos.iter_mut().find(|todo| todo.id == parent_id) {
+                if !todo.depends_on.contains(&child_id) {
+                    todo.depends_on.push(child_id);
+                }
+            }
+        }
+    }
+    (imported, updated)
+}
+
+// the index of the page called `name`, ignoring case, appending a new page if there isn't one
+fn page_index(data: &mut AppData, name: String) -> usize {
+    match data.pages.iter().position(|page| page.name.eq_ignore_ascii_case(&name)) {
+        Some(index) => index,
+        None => {
+            data.pages.push(Page::new(take_id(&mut data.next_id), name));
+            data.pages.len() - 1
+        }
+    }
+}
+
+// the first argument that isn't a flag or the value of one of `flags`
+fn positional<'a>(args: &'a [String], flags: &[&str]) -> Option<&'a str> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if flags.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with("--") {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+fn report(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args.first().map(String::as_str) {
+        Some("time") => report_time(&args[1..]),
+        _ => Err(format!("unknown report\n{}", USAGE).into()),
+    }
+}
+
+// value of `--flag value` or `--flag=value`
+fn flag_value(args: &[String], flag: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return match iter.next() {
+                Some(value) => Ok(Some(value.clone())),
+                None => Err(format!("{} needs a value", flag).into()),
+            };
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Ok(Some(value.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+fn date_flag(args: &[String], flag: &str, today: NaiveDate) -> Result<Option<NaiveDate>, Box<dyn Error>> {
+    match flag_value(args, flag)? {
+        Some(value) => dates::parse_date(&value, today)
+            .map(Some)
+            .ok_or_else(|| format!("invalid date '{}' for {}", value, flag).into()),
+        None => Ok(None),
+    }
+}
+
+struct PageTime {
+    name: String,
+    total: Duration,
+    todos: Vec<TodoTime>,
+}
+
+struct TodoTime {
+    name: String,
+    total: Duration,
+    estimate: Option<i64>,
+}
+
+fn report_time(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let now = Local::now();
+    let today = now.date_naive();
+    let by = flag_value(args, "--by")?;
+    let since = date_flag(args, "--since", today)?;
+    let until = date_flag(args, "--until", today)?;
+
+    if let Some(by) = &by {
+        if !["page", "todo", "day"].contains(&by.as_str()) {
+            return Err(format!("--by must be page, todo or day, not '{}'", by).into());
+        }
+    }
+    let show = |section: &str| by.as_deref().is_none_or(|by| by == section);
+
+    let data = load_app_data()?;
+    let mut by_page: Vec<PageTime> = vec![];
+    let mut by_day: BTreeMap<NaiveDate, Duration> = BTreeMap::new();
+
+    for page in &data.pages {
+        let mut page_total = Duration::zero();
+        let mut todos = vec![];
+        for todo in &page.todos {
+            let mut todo_total = Duration::zero();
+            for entry in &todo.time_log {
+                for (day, duration) in split_by_day(entry.start, entry.end.unwrap_or(now)) {
+                    if since.is_some_and(|since| day < since) || until.is_some_and(|until| day > until) {
+                        continue;
+                    }
+                    todo_total += duration;
+                    *by_day.entry(day).or_insert_with(Duration::zero) += duration;
+                }
+            }
+            if todo_total > Duration::zero() {
+                page_total += todo_total;
+                todos.push(TodoTime {
+                    name: todo.name.clone(),
+                    total: todo_total,
+                    estimate: todo.estimate,
+                });
+            }
+        }
+        if page_total > Duration::zero() {
+            by_page.push(PageTime {
+                name: page.name.clone(),
+                total: page_total,
+                todos,
+            });
+        }
+    }
+
+    if by_page.is_empty() {
+        println!("no tracked time");
+        return Ok(());
+    }
+
+    if show("page") || show("todo") {
+        for page in &by_page {
+            if show("page") {
+                println!("{:>10}  {}", format_duration(page.total), page.name);
+            }
+            if show("todo") {
+                for todo in &page.todos {
+                    let estimate = todo
+                        .estimate
+                        .map(|estimate| format!("  [est {}]", format_estimate(estimate)))
+                        .unwrap_or_default();
+                    if show("page") {
+                        println!("  {:>10}  {}{}", format_duration(todo.total), todo.name, estimate);
+                    } else {
+                        println!("{:>10}  {} ({}){}", format_duration(todo.total), todo.name, page.name, estimate);
+                    }
+                }
+            }
+        }
+    }
+
+    if show("day") {
+        if by.is_none() {
+            println!();
+        }
+        for (day, total) in &by_day {
+            println!("{:>10}  {}", format_duration(*total), day.format("%Y-%m-%d %a"));
+        }
+    }
+
+    if by.is_none() {
+        let total = by_page.iter().fold(Duration::zero(), |sum, page| sum + page.total);
+        println!("\n{:>10}  total", format_duration(total));
+    }
+
+    Ok(())
+}
+
+// splits an interval at local midnights so time is counted on the day it was spent
+fn split_by_day(start: DateTime<Local>, end: DateTime<Local>) -> Vec<(NaiveDate, Duration)> {
+    let mut parts = vec![];
+    let mut start = start;
+    while start < end {
+        let day = start.date_naive();
+        let next_midnight = (day + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .unwrap_or(end);
+        let part_end = next_midnight.min(end);
+        parts.push((day, part_end - start));
+        start = part_end;
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recurrence::Recurrence;
+
+    fn data(pages: &[&str]) -> AppData {
+        let mut data = AppData::default();
+        for name in pages {
+            data.pages.push(Page::new(take_id(&mut data.next_id), name.to_string()));
+        }
+        data
+    }
+
+    #[test]
+    fn hyphenated_projects_map_back_to_their_page() {
+        let data = data(&["work stuff", "home"]);
+        assert_eq!(page_for_project(&data, "work-stuff".to_string()), "work stuff");
+        assert_eq!(page_for_project(&data, "Home".to_string()), "home");
+        assert_eq!(page_for_project(&data, "garden".to_string()), "garden");
+    }
+
+    #[test]
+    fn reimporting_an_export_adds_nothing() {
+        let mut data = data(&["work stuff"]);
+        data.pages[0].todos.push(Todo::new(take_id(&mut data.next_id), "plan review".to_string()));
+        let exported = todotxt::format_line(&data.pages[0].name, &data.pages[0].todos[0]);
+
+        let task = todotxt::parse_line(&exported).unwrap();
+        let page = page_for_project(&data, task.project.unwrap());
+        assert_eq!(merge_todos(&mut data, vec![(page, task.todo)]), (0, 1));
+        assert_eq!(data.pages.len(), 1);
+    }
+
+    #[test]
+    fn merge_keeps_repeated_names_from_one_import() {
+        let mut data = data(&[]);
+        let todos = vec![
+            ("inbox".to_string(), Todo::new(0, "water plants".to_string())),
+            ("Inbox".to_string(), Todo::new(0, "water plants".to_string())),
+        ];
+        assert_eq!(merge_todos(&mut data, todos), (2, 0));
+        assert_eq!(data.pages.len(), 1);
+        assert_eq!(data.pages[0].todos.len(), 2);
+        let ids: Vec<u64> = data.pages[0].todos.iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, [2, 3]);
+    }
+
+    #[test]
+    fn markdown_import_merges_by_name() {
+        let mut data = data(&["Launch"]);
+        data.pages[0].todos.push(Todo::new(take_id(&mut data.next_id), "ship".to_string()));
+
+        let sections = markdown::parse_sections("## launch\n- [x] ship\n  - [ ] tag\n## new\n- [ ] other\n", "inbox");
+        assert_eq!(merge_sections(&mut data, sections), (2, 1));
+
+        let launch = &data.pages[0];
+        assert!(launch.todos[0].completed);
+        assert_eq!(launch.todos[0].depends_on, [launch.todos[1].id]);
+        assert_eq!(data.pages[1].name, "new");
+    }
+
+    #[test]
+    fn markdown_import_completes_recurring_todos_like_the_list() {
+        let mut data = data(&["home"]);
+        let mut todo = Todo::new(take_id(&mut data.next_id), "water plants".to_string());
+        todo.recurrence = Recurrence::parse("daily");
+        data.pages[0].todos.push(todo);
+
+        let sections = markdown::parse_sections("## home\n- [x] water plants\n", "inbox");
+        assert_eq!(merge_sections(&mut data, sections), (0, 1));
+        let todos = &data.pages[0].todos;
+        assert_eq!(todos.len(), 2);
+        assert!(todos[0].completed && todos[0].recurrence.is_none());
+        assert!(!todos[1].completed && todos[1].recurrence.is_some());
+    }
+
+    #[test]
+    fn markdown_import_never_makes_a_cycle() {
+        // b waits on c, which waits on a
+        let mut data = data(&["page"]);
+        for name in ["a", "b", "c"] {
+            data.pages[0].todos.push(Todo::new(take_id(&mut data.next_id), name.to_string()));
+        }
+        let ids: Vec<u64> = data.pages[0].todos.iter().map(|todo| todo.id).collect();
+        data.pages[0].todos[1].depends_on = vec![ids[2]];
+        data.pages[0].todos[2].depends_on = vec![ids[0]];
+
+        // listing b under a would make a wait on b
+        let sections = markdown::parse_sections("## page\n- [ ] a\n  - [ ] b\n", "inbox");
+        merge_sections(&mut data, sections);
+        assert!(data.pages[0].todos[0].depends_on.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+// accepts `2026-03-14`, `today`, `tomorrow`, weekday names (`mon`, `friday`) for the next such
+// day, and offsets like `+3d` or `+2w`. dates outside years 1 to 9999 are rejected so the
+// calendar and recurrences never get near the edge of what chrono can represent
+pub fn parse_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    parse_any_date(input, today).filter(|date| (1..=9999).contains(&date.year()))
+}
+
+fn parse_any_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" | "tod" => return Some(today),
+        "tomorrow" | "tom" => return Some(today + Duration::days(1)),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        return parse_offset(offset).and_then(|days| add_days(today, days));
+    }
+
+    if let Some(weekday) = parse_weekday(&input) {
+        let ahead = (7 + weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64) % 7;
+        let ahead = if ahead == 0 { 7 } else { ahead };
+        return Some(today + Duration::days(ahead));
+    }
+
+    NaiveDate::parse_from_str(&input, "%Y-%m-%d").ok()
+}
+
+// `3d`, `2w`, `1m` (30 days) or a bare number of days
+pub fn parse_offset(input: &str) -> Option<i64> {
+    let input = input.trim();
+    let (number, multiplier) = match input.chars().last()? {
+        'd' => (&input[..input.len() - 1], 1),
+        'w' => (&input[..input.len() - 1], 7),
+        'm' => (&input[..input.len() - 1], 30),
+        _ => (input, 1),
+    };
+    number.trim().parse::<i64>().ok()?.checked_mul(multiplier)
+}
+
+// `None` instead of a panic when the result is past the dates chrono can represent
+pub fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    date.checked_add_signed(Duration::try_days(days)?)
+}
+
+pub fn parse_weekday(input: &str) -> Option<Weekday> {
+    let weekday = match input.trim().to_lowercase().as_str() {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+// short label relative to today: `today`, `tomorrow`, `fri 20`, `overdue 3d` or the full date
+pub fn format_due(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (date - today).num_days();
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        d if d < 0 => format!("overdue {}d", -d),
+        d if d < 7 => format!("{} {}", weekday_name(date.weekday()), date.day()),
+        _ => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_named_days() {
+        // a saturday
+        let today = date("2026-03-14");
+        assert_eq!(parse_date("today", today), Some(today));
+        assert_eq!(parse_date("Tomorrow", today), Some(date("2026-03-15")));
+        assert_eq!(parse_date("yesterday", today), Some(date("2026-03-13")));
+        assert_eq!(parse_date("2026-04-01", today), Some(date("2026-04-01")));
+        assert_eq!(parse_date("next week", today), None);
+    }
+
+    #[test]
+    fn weekdays_are_always_ahead() {
+        let today = date("2026-03-14");
+        assert_eq!(parse_date("mon", today), Some(date("2026-03-16")));
+        assert_eq!(parse_date("friday", today), Some(date("2026-03-20")));
+        assert_eq!(parse_date("sat", today), Some(date("2026-03-21")));
+    }
+
+    #[test]
+    fn parses_offsets() {
+        let today = date("2026-03-14");
+        assert_eq!(parse_offset("3"), Some(3));
+        assert_eq!(parse_offset("2w"), Some(14));
+        assert_eq!(parse_offset("1m"), Some(30));
+        assert_eq!(parse_offset("w"), None);
+        assert_eq!(parse_date("+3d", today), Some(date("2026-03-17")));
+        assert_eq!(parse_date("+2w", today), Some(date("2026-03-28")));
+    }
+
+    #[test]
+    fn formats_due_dates_relative_to_today() {
+        let today = date("2026-03-14");
+        assert_eq!(format_due(today, today), "today");
+        assert_eq!(format_due(date("2026-03-15"), today), "tomorrow");
+        assert_eq!(format_due(date("2026-03-11"), today), "overdue 3d");
+        assert_eq!(format_due(date("2026-03-20"), today), "fri 20");
+        assert_eq!(format_due(date("2026-04-01"), today), "2026-04-01");
+    }
+
+    #[test]
+    fn huge_offsets_are_invalid() {
+        let today = date("2026-03-14");
+        assert_eq!(parse_offset(&format!("{}w", i64::MAX)), None);
+        assert_eq!(parse_date("+99999999d", today), None);
+        assert_eq!(parse_date(&format!("+{}d", i64::MAX), today), None);
+        assert_eq!(parse_date("+9999999d", today), None);
+        assert_eq!(add_days(NaiveDate::MAX, 1), None);
+    }
+}
@@ -0,0 +1,376 @@
+use chrono::{Local, NaiveDate};
+
+use crate::{dates, parse_estimate, Page, Priority, Todo};
+
+// a parsed filter expression such as `tag:work and not done and due<7d`
+#[derive(Clone)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Done,
+    Recurring,
+    Tag(String),
+    Page(String),
+    Text(String),
+    // dates are kept as typed so relative ones like `7d` stay relative in saved filters
+    Due(Comparison, Option<String>),
+    Priority(Comparison, Option<Priority>),
+    Estimate(Comparison, i64),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn test<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+impl Filter {
+    // terms next to each other are joined with `and`; `or` binds looser and parentheses group
+    pub fn parse(input: &str) -> Result<Filter, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0 };
+        if parser.tokens.is_empty() {
+            return Err("empty filter".to_string());
+        }
+        let filter = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(format!("unexpected '{}'", token.text())),
+        }
+    }
+
+    pub fn matches(&self, page: &Page, todo: &Todo, today: NaiveDate) -> bool {
+        match self {
+            Filter::And(left, right) => left.matches(page, todo, today) && right.matches(page, todo, today),
+            Filter::Or(left, right) => left.matches(page, todo, today) || right.matches(page, todo, today),
+            Filter::Not(inner) => !inner.matches(page, todo, today),
+            Filter::Done => todo.completed,
+            Filter::Recurring => todo.recurrence.is_some(),
+            Filter::Tag(tag) => tags(&todo.name).any(|t| t.eq_ignore_ascii_case(tag)),
+            Filter::Page(name) => page.name.eq_ignore_ascii_case(name),
+            Filter::Text(text) => {
+                let text = text.to_lowercase();
+                todo.name.to_lowercase().contains(&text) || todo.notes.to_lowercase().contains(&text)
+            }
+            Filter::Due(comparison, None) => match comparison {
+                Comparison::Eq => todo.due.is_none(),
+                _ => false,
+            },
+            Filter::Due(comparison, Some(value)) => match (todo.due, resolve_date(value, today)) {
+                (Some(due), Some(date)) => comparison.test(due, date),
+                _ => false,
+            },
+            Filter::Priority(comparison, None) => *comparison == Comparison::Eq && todo.priority.is_none(),
+            Filter::Priority(comparison, Some(priority)) => {
+                todo.priority.is_some_and(|todo_priority| comparison.test(todo_priority, *priority))
+            }
+            Filter::Estimate(comparison, minutes) => {
+                todo.estimate.is_some_and(|estimate| comparison.test(estimate, *minutes))
+            }
+        }
+    }
+}
+
+// the `#word` tags in a todo's name, without the `#`
+pub fn tags(name: &str) -> impl Iterator<Item = &str> {
+    name.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-' && c != '_'))
+        .filter(|tag| !tag.is_empty())
+}
+
+// a bare offset such as `7d` counts from today, anything else is read like a due date
+fn resolve_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let looks_like_offset = value.chars().next().is_some_and(|c| c.is_ascii_digit()) && !value.contains('-');
+    if looks_like_offset {
+        return dates::parse_offset(value).and_then(|days| dates::add_days(today, days));
+    }
+    dates::parse_date(value, today)
+}
+
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    // always searched for as text, never read as a keyword or field
+    Quoted(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::Word(word) | Token::Quoted(word) => word,
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+// words are split on whitespace and parentheses. a token that starts with double quotes is
+// text to search for, while quotes after a field like `page:"work stuff"` keep spaces in its value
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err("unclosed quote".to_string()),
+                    }
+                }
+                if word.is_empty() {
+                    tokens.push(Token::Quoted(quoted));
+                } else {
+                    word.push_str(&quoted);
+                }
+            }
+            '(' | ')' => {
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                }
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_and()?;
+        while self.peek().is_some_and(|token| token.is_keyword("or")) {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(token) if token.is_keyword("or") => break,
+                Some(token) if token.is_keyword("and") => self.position += 1,
+                _ => {}
+            }
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            None => Err("filter ends early".to_string()),
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(token) if token.is_keyword("not") => Ok(Filter::Not(Box::new(self.parse_unary()?))),
+            Some(Token::Quoted(text)) => Ok(Filter::Text(text.clone())),
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                parse_term(&word)
+            }
+        }
+    }
+}
+
+fn parse_term(word: &str) -> Result<Filter, String> {
+    match word.to_lowercase().as_str() {
+        "done" | "completed" => return Ok(Filter::Done),
+        "open" | "todo" => return Ok(Filter::Not(Box::new(Filter::Done))),
+        "recurring" => return Ok(Filter::Recurring),
+        "overdue" => {
+            return Ok(Filter::And(
+                Box::new(Filter::Not(Box::new(Filter::Done))),
+                Box::new(Filter::Due(Comparison::Lt, Some("today".to_string()))),
+            ));
+        }
+        _ => {}
+    }
+    if let Some(tag) = word.strip_prefix('#') {
+        return Ok(Filter::Tag(tag.to_string()));
+    }
+
+    let Some(split) = word.find([':', '<', '>', '=']) else {
+        return Ok(Filter::Text(word.to_string()));
+    };
+    let (key, rest) = word.split_at(split);
+    let (comparison, value) = if let Some(value) = rest.strip_prefix("<=") {
+        (Comparison::Le, value)
+    } else if let Some(value) = rest.strip_prefix(">=") {
+        (Comparison::Ge, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Comparison::Lt, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparison::Gt, value)
+    } else {
+        (Comparison::Eq, &rest[1..])
+    };
+    if value.is_empty() {
+        return Err(format!("'{}' needs a value", key));
+    }
+
+    let only_equals = |filter: Filter| {
+        if comparison == Comparison::Eq {
+            Ok(filter)
+        } else {
+            Err(format!("'{}' can only be matched with ':'", key))
+        }
+    };
+    match key.to_lowercase().as_str() {
+        "tag" => only_equals(Filter::Tag(value.trim_start_matches('#').to_string())),
+        "page" => only_equals(Filter::Page(value.to_string())),
+        "text" | "name" => only_equals(Filter::Text(value.to_string())),
+        "due" if value.eq_ignore_ascii_case("none") => only_equals(Filter::Due(Comparison::Eq, None)),
+        "due" => match resolve_date(value, Local::now().date_naive()) {
+            Some(_) => Ok(Filter::Due(comparison, Some(value.to_lowercase()))),
+            None => Err(format!("invalid date '{}'", value)),
+        },
+        "priority" | "pri" if value.eq_ignore_ascii_case("none") => only_equals(Filter::Priority(Comparison::Eq, None)),
+        "priority" | "pri" => match Priority::parse(value) {
+            Some(priority) => Ok(Filter::Priority(comparison, Some(priority))),
+            None => Err(format!("invalid priority '{}'", value)),
+        },
+        "estimate" | "est" => match parse_estimate(value) {
+            Some(minutes) => Ok(Filter::Estimate(comparison, minutes)),
+            None => Err(format!("invalid estimate '{}'", value)),
+        },
+        _ => Err(format!("unknown field '{}'", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 14).unwrap()
+    }
+
+    fn todo(name: &str) -> Todo {
+        Todo::new(0, name.to_string())
+    }
+
+    fn matches(filter: &str, page: &Page, todo: &Todo) -> bool {
+        Filter::parse(filter).unwrap().matches(page, todo, today())
+    }
+
+    #[test]
+    fn combines_terms() {
+        let page = Page::new(0, "Work".to_string());
+        let mut report = todo("write report #work");
+        report.priority = Some(Priority::High);
+        report.due = NaiveDate::from_ymd_opt(2026, 3, 16);
+
+        assert!(matches("tag:work and not done", &page, &report));
+        assert!(matches("#WORK page:work", &page, &report));
+        assert!(matches("done or priority>=medium", &page, &report));
+        assert!(!matches("done or (priority<high and report)", &page, &report));
+        assert!(matches("due<7d due>today", &page, &report));
+        assert!(!matches("due:none", &page, &report));
+        assert!(matches("REPORT", &page, &report));
+    }
+
+    #[test]
+    fn reports_invalid_filters() {
+        for (input, error) in [
+            ("", "empty filter"),
+            ("(done", "missing ')'"),
+            ("done)", "unexpected ')'"),
+            ("not", "filter ends early"),
+            ("\"open", "unclosed quote"),
+            ("color:red", "unknown field 'color'"),
+            ("tag<work", "'tag' can only be matched with ':'"),
+            ("priority:urgent", "invalid priority 'urgent'"),
+            ("est>soon", "invalid estimate 'soon'"),
+        ] {
+            assert_eq!(Filter::parse(input).err().as_deref(), Some(error), "{}", input);
+        }
+    }
+
+    #[test]
+    fn huge_due_offsets_are_invalid() {
+        assert_eq!(Filter::parse("due<99999999d").err().as_deref(), Some("invalid date '99999999d'"));
+        assert_eq!(resolve_date("99999999d", today()), None);
+        assert_eq!(resolve_date(&format!("{}w", i64::MAX), today()), None);
+    }
+
+    #[test]
+    fn quoted_text_is_only_searched_for() {
+        let page = Page::new(0, "inbox".to_string());
+        let meeting = todo("meeting at 12:30 or later");
+        let done = todo("get it done");
+
+        assert!(matches("\"12:30\"", &page, &meeting));
+        assert!(matches("\"at 12:30 or\"", &page, &meeting));
+        assert!(!matches("\"or\" \"12:00\"", &page, &meeting));
+        assert!(matches("\"done\"", &page, &done));
+        assert!(!matches("\"done\"", &page, &meeting));
+    }
+
+    #[test]
+    fn quotes_keep_spaces_in_field_values() {
+        let page = Page::new(0, "work stuff".to_string());
+        assert!(matches("page:\"work stuff\"", &page, &todo("anything")));
+    }
+
+    #[test]
+    fn finds_tags() {
+        let tags: Vec<&str> = tags("fix #bug, then #follow-up and #a_b! but not # or a#b").collect();
+        assert_eq!(tags, ["bug", "follow-up", "a_b"]);
+    }
+}
@@ -1,24 +1,37 @@
+mod cli;
+mod dates;
+mod filter;
+mod markdown;
+mod recurrence;
+mod todotxt;
+
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{hash_map::RandomState, HashMap},
     env,
     error::Error,
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
+    hash::{BuildHasher, Hasher},
     io::{self, BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
+    process::Command,
     time::{Duration, Instant},
 };
+use filter::Filter;
+use recurrence::Recurrence;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::{Line, Span},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
     widgets::{
-        Block, Borders, List, ListItem, ListState, Paragraph,
+        Bar, BarChart, BarGroup, Block, Borders, Clear, LineGauge, List, ListItem, ListState, Paragraph, Row, Table,
         Scrollbar, ScrollbarState, ScrollbarOrientation,
     },
     Frame, Terminal,
@@ -26,14 +39,436 @@ use ratatui::{
 
 #[derive(Serialize, Deserialize, Clone)]
 struct Page {
+    // 0 until one is assigned on load
+    #[serde(default)]
+    id: u64,
     name: String,
+    // todos are always stored in manual order and sorted for display
     todos: Vec<Todo>,
+    #[serde(default, skip_serializing_if = "SortMode::is_manual")]
+    sort: SortMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+enum SortMode {
+    #[default]
+    Manual,
+    Alpha,
+    OpenFirst,
+    Due,
+    Priority,
+    Created,
+}
+
+impl SortMode {
+    fn is_manual(&self) -> bool {
+        *self == SortMode::Manual
+    }
+
+    fn next(self) -> SortMode {
+        match self {
+            SortMode::Manual => SortMode::Alpha,
+            SortMode::Alpha => SortMode::OpenFirst,
+            SortMode::OpenFirst => SortMode::Due,
+            SortMode::Due => SortMode::Priority,
+            SortMode::Priority => SortMode::Created,
+            SortMode::Created => SortMode::Manual,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Alpha => "a-z",
+            SortMode::OpenFirst => "open first",
+            SortMode::Due => "due",
+            SortMode::Priority => "priority",
+            SortMode::Created => "newest",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    // none -> low -> medium -> high -> none
+    fn cycle(priority: Option<Priority>) -> Option<Priority> {
+        match priority {
+            None => Some(Priority::Low),
+            Some(Priority::Low) => Some(Priority::Medium),
+            Some(Priority::Medium) => Some(Priority::High),
+            Some(Priority::High) => None,
+        }
+    }
+
+    fn parse(input: &str) -> Option<Priority> {
+        match input.trim().to_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "medium" | "med" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        }
+    }
+
+    fn marker(self) -> &'static str {
+        match self {
+            Priority::Low => "!",
+            Priority::Medium => "!!",
+            Priority::High => "!!!",
+        }
+    }
+}
+
+impl Page {
+    fn new(id: u64, name: String) -> Page {
+        Page {
+            id,
+            name,
+            todos: vec![],
+            sort: SortMode::Manual,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 struct Todo {
+    // 0 until one is assigned on load
+    #[serde(default)]
+    id: u64,
     name: String,
     completed: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurrence: Option<Recurrence>,
+    // board column for open todos; None means the first column. completed todos are
+    // always in the last column, so files without statuses still map onto the board
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    time_log: Vec<TimeEntry>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pomodoros: u32,
+    // estimated effort in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<Priority>,
+    // ids of the todos that have to be completed first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<u64>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TimeEntry {
+    start: DateTime<Local>,
+    // None while the timer is running
+    end: Option<DateTime<Local>>,
+}
+
+impl Todo {
+    fn new(id: u64, name: String) -> Todo {
+        let now = Local::now();
+        Todo {
+            id,
+            name,
+            completed: false,
+            notes: String::new(),
+            created_at: Some(now),
+            completed_at: None,
+            modified_at: Some(now),
+            due: None,
+            recurrence: None,
+            status: None,
+            time_log: vec![],
+            pomodoros: 0,
+            estimate: None,
+            priority: None,
+            depends_on: vec![],
+        }
+    }
+
+    fn touch(&mut self) {
+        self.modified_at = Some(Local::now());
+    }
+
+    fn set_completed(&mut self, completed: bool) {
+        if self.completed == completed {
+            return;
+        }
+        self.completed = completed;
+        self.completed_at = if completed { Some(Local::now()) } else { None };
+        self.status = None;
+        if completed {
+            self.stop_timer();
+        }
+        self.touch();
+    }
+
+    fn is_timing(&self) -> bool {
+        self.time_log.last().is_some_and(|entry| entry.end.is_none())
+    }
+
+    fn start_timer(&mut self) {
+        if !self.is_timing() {
+            self.time_log.push(TimeEntry { start: Local::now(), end: None });
+        }
+    }
+
+    fn stop_timer(&mut self) {
+        if let Some(entry) = self.time_log.last_mut().filter(|entry| entry.end.is_none()) {
+            entry.end = Some(Local::now());
+        }
+    }
+
+    fn tracked_time(&self) -> chrono::Duration {
+        let now = Local::now();
+        self.time_log
+            .iter()
+            .fold(chrono::Duration::zero(), |total, entry| total + (entry.end.unwrap_or(now) - entry.start))
+    }
+
+    // completes a recurring todo and returns its next occurrence, which takes over the
+    // recurrence so reopening the completed one doesn't spawn another. a series whose next
+    // date can't be represented ends here. the next occurrence's id is taken from `next_id`
+    fn complete_occurrence(&mut self, next_id: &mut u64) -> Option<Todo> {
+        let recurrence = self.recurrence.take()?;
+        let today = Local::now().date_naive();
+        self.set_completed(true);
+        let due = recurrence.next_due(self.due, today)?;
+
+        let mut next = Todo::new(take_id(next_id), self.name.clone());
+        next.notes = self.notes.clone();
+        next.estimate = self.estimate;
+        next.priority = self.priority;
+        next.due = Some(due);
+        next.recurrence = Some(recurrence);
+        Some(next)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+enum TrashedItem {
+    Todo {
+        page: String,
+        // 0 in entries trashed before pages had ids, which are restored by page name
+        #[serde(default)]
+        page_id: u64,
+        position: usize,
+        todo: Box<Todo>,
+    },
+    Page { position: usize, page: Page },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TrashEntry {
+    item: TrashedItem,
+    deleted_at: DateTime<Local>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct Settings {
+    // 0 keeps trashed items forever
+    trash_retention_days: i64,
+
+    confirm_delete_todo: bool,
+    confirm_delete_page: bool,
+    confirm_purge: bool,
+    // asks before completing a todo whose dependencies are still open
+    confirm_complete_blocked: bool,
+
+    page_layout: PageLayout,
+    show_details: bool,
+    show_ages: bool,
+    hide_completed: bool,
+
+    // the last column holds completed todos
+    board_columns: Vec<String>,
+
+    pomodoro_work_minutes: i64,
+    pomodoro_break_minutes: i64,
+
+    // moves todos to the bottom of the page when they are completed
+    sink_completed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PageLayout {
+    Tabs,
+    Sidebar,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            trash_retention_days: 30,
+
+            confirm_delete_todo: false,
+            confirm_delete_page: true,
+            confirm_purge: true,
+            confirm_complete_blocked: true,
+
+            page_layout: PageLayout::Tabs,
+            show_details: false,
+            show_ages: false,
+            hide_completed: false,
+
+            board_columns: vec!["todo".to_string(), "doing".to_string(), "done".to_string()],
+
+            pomodoro_work_minutes: 25,
+            pomodoro_break_minutes: 5,
+
+            sink_completed: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+struct Pomodoro {
+    todo: u64,
+    phase: PomodoroPhase,
+    started_at: DateTime<Local>,
+    ends_at: DateTime<Local>,
+}
+
+#[derive(Clone, Copy)]
+enum EditorRequest {
+    Notes(usize),
+    Page(usize),
+}
+
+#[derive(Clone, Copy)]
+enum PendingAction {
+    ToggleTodo(usize),
+    DeleteTodo(usize),
+    DeletePage(usize),
+    DeleteView(usize),
+    PurgeTrashEntry(usize),
+    PurgeTrash,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct AppData {
+    pages: Vec<Page>,
+    #[serde(default)]
+    trash: Vec<TrashEntry>,
+    #[serde(default)]
+    settings: Settings,
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    views: Vec<SavedView>,
+}
+
+// a named filter shown as a tab after the pages
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedView {
+    name: String,
+    filter: String,
+}
+
+// hands out the next id from the counter saved with the data. pages and todos share it,
+// and 0 is never used since it marks ids still to be assigned
+fn take_id(next_id: &mut u64) -> u64 {
+    let id = (*next_id).max(1);
+    *next_id = id + 1;
+    id
+}
+
+#[derive(Serialize)]
+struct AppDataRef<'a> {
+    pages: &'a [Page],
+    trash: &'a [TrashEntry],
+    settings: &'a Settings,
+    next_id: u64,
+    views: &'a [SavedView],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    Todos,
+    Agenda,
+    Calendar,
+    Board,
+    Stats,
+    Filter,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum AgendaGroup {
+    Overdue,
+    Today,
+    Tomorrow,
+    ThisWeek,
+    Later,
+    NoDate,
+}
+
+impl AgendaGroup {
+    fn of(due: Option<NaiveDate>, today: NaiveDate) -> AgendaGroup {
+        let Some(due) = due else {
+            return AgendaGroup::NoDate;
+        };
+        match (due - today).num_days() {
+            d if d < 0 => AgendaGroup::Overdue,
+            0 => AgendaGroup::Today,
+            1 => AgendaGroup::Tomorrow,
+            d if d < 7 => AgendaGroup::ThisWeek,
+            _ => AgendaGroup::Later,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AgendaGroup::Overdue => "overdue",
+            AgendaGroup::Today => "today",
+            AgendaGroup::Tomorrow => "tomorrow",
+            AgendaGroup::ThisWeek => "this week",
+            AgendaGroup::Later => "later",
+            AgendaGroup::NoDate => "no date",
+        }
+    }
+}
+
+struct AgendaEntry {
+    group: AgendaGroup,
+    due: Option<NaiveDate>,
+    page_index: usize,
+    todo_index: usize,
 }
 
 const TICK_RATE_MS: u64 = 250;
@@ -56,6 +491,59 @@ struct App {
     is_renaming_todo: bool,
     rename_todo_input: String,
 
+    is_setting_due: bool,
+    due_input: String,
+
+    is_setting_recurrence: bool,
+    recurrence_input: String,
+
+    is_setting_estimate: bool,
+    estimate_input: String,
+
+    is_filtering: bool,
+    filter_input: String,
+
+    is_naming_view: bool,
+    view_name_input: String,
+
+    // set when the current prompt's input couldn't be parsed
+    input_error: bool,
+
+    is_viewing_trash: bool,
+    selected_trash_index: usize,
+
+    view: View,
+    selected_agenda_index: usize,
+    calendar_date: NaiveDate,
+    board_column_index: usize,
+    board_row_index: usize,
+    // the filter shown in the filter view and the text it was parsed from
+    filter: Option<Filter>,
+    filter_query: String,
+    selected_filter_index: usize,
+
+    views: Vec<SavedView>,
+    // index of the saved view open in the filter view, if it came from one
+    current_view: Option<usize>,
+
+    // the id the next new page or todo gets
+    next_id: u64,
+
+    pending_action: Option<PendingAction>,
+    editor_request: Option<EditorRequest>,
+
+    // id of the todo that the next selected todo will become a dependency of
+    linking_todo: Option<u64>,
+
+    pomodoro: Option<Pomodoro>,
+
+    is_picking_page: bool,
+    page_picker_input: String,
+    selected_picker_index: usize,
+
+    trash: Vec<TrashEntry>,
+    settings: Settings,
+
     should_quit: bool,
 
     scrollbar_state: ScrollbarState,
@@ -66,18 +554,19 @@ struct App {
 }
 
 impl App {
-    fn new() -> App {
-        let mut pages = load_app_data().unwrap_or_else(|_| vec![]);
+    fn new() -> Result<App, Box<dyn Error>> {
+        Ok(App::from_data(load_app_data()?))
+    }
+
+    fn from_data(data: AppData) -> App {
+        let AppData { mut pages, trash, settings, mut next_id, views } = data;
         if pages.is_empty() {
-            pages.push(Page {
-                name: "main".to_string(),
-                todos: vec![],
-            });
+            pages.push(Page::new(take_id(&mut next_id), "main".to_string()));
         }
 
         let context_prefix = get_context_prefix();
 
-        App {
+        let mut app = App {
             pages,
             current_page_index: 0,
 
@@ -95,13 +584,65 @@ impl App {
             is_renaming_todo: false,
             rename_todo_input: String::new(),
 
+            is_setting_due: false,
+            due_input: String::new(),
+
+            is_setting_recurrence: false,
+            recurrence_input: String::new(),
+            is_setting_estimate: false,
+            estimate_input: String::new(),
+            is_filtering: false,
+            filter_input: String::new(),
+            is_naming_view: false,
+            view_name_input: String::new(),
+
+            input_error: false,
+
+            is_viewing_trash: false,
+            selected_trash_index: 0,
+
+            view: View::Todos,
+            selected_agenda_index: 0,
+            calendar_date: Local::now().date_naive(),
+            board_column_index: 0,
+            board_row_index: 0,
+            filter: None,
+            filter_query: String::new(),
+            selected_filter_index: 0,
+
+            views,
+            current_view: None,
+
+            next_id,
+
+            pending_action: None,
+            editor_request: None,
+
+            linking_todo: None,
+
+            pomodoro: None,
+
+            is_picking_page: false,
+            page_picker_input: String::new(),
+            selected_picker_index: 0,
+
+            trash,
+            settings,
+
             should_quit: false,
 
             scrollbar_state: ScrollbarState::default(),
 
             context_prefix,
             cursor_position: 0,
+        };
+
+        if app.purge_expired_trash() {
+            app.save_app_data().ok();
         }
+        app.clamp_selection();
+
+        app
     }
 
     fn current_page(&self) -> &Page {
@@ -117,127 +658,817 @@ impl App {
     }
 
     fn save_app_data(&self) -> Result<(), Box<dyn Error>> {
-        save_app_data(&self.pages)
+        // tests build an App from in-memory data and must never write over the real file
+        if cfg!(test) {
+            return Ok(());
+        }
+        save_app_data(&AppDataRef {
+            pages: &self.pages,
+            trash: &self.trash,
+            settings: &self.settings,
+            next_id: self.next_id,
+            views: &self.views,
+        })
     }
 
-    fn update_scrollbar(&mut self, list_height: usize) {
-        let current_todos_len = self.current_todos().len();
-        if current_todos_len > 0 {
-            self.scrollbar_state = ScrollbarState::default()
-                .content_length(current_todos_len)
-                .viewport_content_length(list_height)
-                .position(self.selected_todo_index);
+    fn start_renaming_todo(&mut self) {
+        self.rename_todo_input = self.current_todos()[self.selected_todo_index].name.clone();
+        self.cursor_position = self.rename_todo_input.len();
+        self.is_renaming_todo = true;
+    }
+
+    fn start_setting_due(&mut self) {
+        self.due_input = self.current_todos()[self.selected_todo_index]
+            .due
+            .map(|due| due.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        self.cursor_position = self.due_input.len();
+        self.is_setting_due = true;
+    }
+
+    fn start_setting_recurrence(&mut self) {
+        self.recurrence_input = self.current_todos()[self.selected_todo_index]
+            .recurrence
+            .as_ref()
+            .map(Recurrence::to_string)
+            .unwrap_or_default();
+        self.cursor_position = self.recurrence_input.len();
+        self.is_setting_recurrence = true;
+    }
+
+    fn start_setting_estimate(&mut self) {
+        self.estimate_input = self.current_todos()[self.selected_todo_index]
+            .estimate
+            .map(format_estimate)
+            .unwrap_or_default();
+        self.cursor_position = self.estimate_input.len();
+        self.is_setting_estimate = true;
+    }
+
+    fn start_filtering(&mut self) {
+        // a filter started outside the filter view is a new one, not an edit of the last view
+        if self.view != View::Filter {
+            self.current_view = None;
         }
+        self.filter_input = self.filter_query.clone();
+        self.cursor_position = self.filter_input.len();
+        self.is_filtering = true;
     }
 
-    fn process_input_event(&mut self, key: KeyEvent) -> bool {
-        if self.is_creating_todo {
-            match key.code {
-                KeyCode::Down => {
-                    if !self.current_todos().is_empty() {
-                        self.selected_todo_index = (self.selected_todo_index + 1) % self.current_todos().len();
-                    }
-                    return true;
-                }
-                KeyCode::Up => {
-                    if !self.current_todos().is_empty() {
-                        self.selected_todo_index = (self.selected_todo_index + self.current_todos().len() - 1) % self.current_todos().len();
-                    }
-                    return true;
+    // tabs are the pages followed by the saved views
+    fn current_tab(&self) -> usize {
+        match self.current_view {
+            Some(index) if self.view == View::Filter => self.pages.len() + index,
+            _ => self.current_page_index,
+        }
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        if tab < self.pages.len() {
+            self.current_page_index = tab;
+            self.selected_todo_index = 0;
+            self.current_view = None;
+            self.view = View::Todos;
+        } else if let Some(view) = self.views.get(tab - self.pages.len()) {
+            // a filter that no longer parses shows no todos rather than failing
+            self.filter = Filter::parse(&view.filter).ok();
+            self.filter_query = view.filter.clone();
+            self.selected_filter_index = 0;
+            self.current_view = Some(tab - self.pages.len());
+            self.view = View::Filter;
+        }
+    }
+
+    fn step_tab(&mut self, forward: bool) {
+        let tabs = self.pages.len() + self.views.len();
+        let current = self.current_tab();
+        self.select_tab(if forward { (current + 1) % tabs } else { (current + tabs - 1) % tabs });
+    }
+
+    fn start_naming_view(&mut self) {
+        self.view_name_input = self
+            .current_view
+            .map(|index| self.views[index].name.clone())
+            .unwrap_or_default();
+        self.cursor_position = self.view_name_input.len();
+        self.is_naming_view = true;
+    }
+
+    // (page index, todo index) of every todo matching the active filter
+    fn filter_entries(&self) -> Vec<(usize, usize)> {
+        let Some(filter) = &self.filter else {
+            return vec![];
+        };
+        let today = Local::now().date_naive();
+        let mut entries = vec![];
+        for (page_index, page) in self.pages.iter().enumerate() {
+            for (todo_index, todo) in page.todos.iter().enumerate() {
+                if filter.matches(page, todo, today) {
+                    entries.push((page_index, todo_index));
                 }
-                _ => {}
             }
+        }
+        entries
+    }
 
-            match Self::edit_buffer(&mut self.new_todo_input, &mut self.cursor_position, key) {
-                EditResult::Enter => {
-                    let name: String = self.new_todo_input.drain(..).collect();
-                    if !name.is_empty() {
-                        self.current_todos_mut().push(Todo { name, completed: false });
-                        self.selected_todo_index = self.current_todos().len() - 1;
-                        self.save_app_data().ok();
-                    }
-                    self.is_creating_todo = false;
-                    self.cursor_position = 0;
-                }
-                EditResult::Esc => {
-                    self.is_creating_todo = false;
-                    self.new_todo_input.clear();
-                    self.cursor_position = 0;
+    // open todos from every page, plus those completed today so toggling can be undone
+    fn agenda_entries(&self) -> Vec<AgendaEntry> {
+        let today = Local::now().date_naive();
+        let mut entries = vec![];
+        for (page_index, page) in self.pages.iter().enumerate() {
+            for (todo_index, todo) in page.todos.iter().enumerate() {
+                let completed_today = todo.completed_at.is_some_and(|at| at.date_naive() == today);
+                if todo.completed && !completed_today {
+                    continue;
                 }
-                EditResult::None => {}
+                entries.push(AgendaEntry {
+                    group: AgendaGroup::of(todo.due, today),
+                    due: todo.due,
+                    page_index,
+                    todo_index,
+                });
             }
+        }
+        entries.sort_by_key(|entry| (entry.group, entry.due, entry.page_index, entry.todo_index));
+        entries
+    }
 
-            return true;
+    // (page index, todo index) of every todo due on the given day
+    fn todos_due_on(&self, date: NaiveDate) -> Vec<(usize, usize)> {
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(|(page_index, page)| {
+                page.todos
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, todo)| todo.due == Some(date))
+                    .map(move |(todo_index, _)| (page_index, todo_index))
+            })
+            .collect()
+    }
+
+    // keeps the current date when the new one, or the month drawn around it, is out of range
+    fn move_calendar(&mut self, date: Option<NaiveDate>) {
+        if let Some(date) = date.filter(|&date| calendar_grid(date).is_some()) {
+            self.calendar_date = date;
         }
+    }
 
-        if self.is_creating_page {
-            match Self::edit_buffer(&mut self.new_page_name_input, &mut self.cursor_position, key) {
-                EditResult::Enter => {
-                    let page_name = if self.new_page_name_input.is_empty() {
-                        "".to_string()
-                    } else {
-                        self.new_page_name_input.drain(..).collect()
-                    };
-                    self.pages.push(Page { name: page_name, todos: vec![] });
-                    self.current_page_index = self.pages.len() - 1;
-                    self.selected_todo_index = 0;
-                    self.is_creating_page = false;
-                    self.cursor_position = 0;
-                    self.save_app_data().ok();
-                }
-                EditResult::Esc => {
-                    self.is_creating_page = false;
-                    self.new_page_name_input.clear();
-                    self.cursor_position = 0;
-                }
-                EditResult::None => {}
-            }
-            return true;
+    fn board_columns(&self) -> Vec<String> {
+        if self.settings.board_columns.len() >= 2 {
+            self.settings.board_columns.clone()
+        } else {
+            Settings::default().board_columns
         }
+    }
 
-        if self.is_renaming_page {
-            match Self::edit_buffer(&mut self.rename_page_input, &mut self.cursor_position, key) {
-                EditResult::Enter => {
-                    if self.rename_page_input.is_empty() {
-                        if self.pages.len() > 1 {
-                            self.pages.remove(self.current_page_index);
-                            if self.current_page_index >= self.pages.len() {
-                                self.current_page_index = self.pages.len() - 1;
-                            }
-                            self.selected_todo_index = 0;
-                        }
-                    } else {
-                        self.pages[self.current_page_index].name = self.rename_page_input.drain(..).collect();
-                    }
-                    self.is_renaming_page = false;
-                    self.rename_page_input.clear();
-                    self.save_app_data().ok();
-                }
-                EditResult::Esc => {
-                    self.is_renaming_page = false;
-                    self.rename_page_input.clear();
-                }
-                EditResult::None => {}
-            }
-            return true;
+    fn board_column_of(&self, todo: &Todo, columns: &[String]) -> usize {
+        let last = columns.len() - 1;
+        if todo.completed {
+            return last;
         }
+        todo.status
+            .as_ref()
+            .and_then(|status| columns.iter().position(|column| column == status))
+            .filter(|&column| column != last)
+            .unwrap_or(0)
+    }
 
-        if self.is_renaming_todo {
+    // todo indices on the current page in each board column
+    fn board_cards(&self) -> Vec<Vec<usize>> {
+        let columns = self.board_columns();
+        let mut cards = vec![vec![]; columns.len()];
+        for (i, todo) in self.current_todos().iter().enumerate() {
+            cards[self.board_column_of(todo, &columns)].push(i);
+        }
+        cards
+    }
+
+    fn move_to_board_column(&mut self, index: usize, column: usize) {
+        let columns = self.board_columns();
+        let last = columns.len() - 1;
+        let todo = &mut self.pages[self.current_page_index].todos[index];
+        if column == last && !todo.completed && todo.recurrence.is_some() {
+            // completed the same way as from the list so the next occurrence is scheduled
+            if let Some(next) = todo.complete_occurrence(&mut self.next_id) {
+                self.current_todos_mut().insert(index + 1, next);
+            }
+        } else {
+            todo.set_completed(column == last);
+        }
+
+        let todo = &mut self.current_todos_mut()[index];
+        todo.status = if column == 0 || column == last {
+            None
+        } else {
+            Some(columns[column].clone())
+        };
+        todo.touch();
+    }
+
+    fn todo_by_id(&self, id: u64) -> Option<&Todo> {
+        find_todo(&self.pages, id).map(|(page_index, todo_index)| &self.pages[page_index].todos[todo_index])
+    }
+
+    // open dependencies of the todo. deleted ones no longer block it
+    fn blockers(&self, todo: &Todo) -> Vec<&Todo> {
+        todo.depends_on
+            .iter()
+            .filter_map(|id| self.todo_by_id(*id))
+            .filter(|dependency| !dependency.completed)
+            .collect()
+    }
+
+    // makes `todo` depend on `dependency`, or removes the dependency if it exists.
+    // returns false if the link would make a todo wait on itself
+    fn toggle_dependency(&mut self, todo: u64, dependency: u64) -> bool {
+        let Some((page_index, todo_index)) = find_todo(&self.pages, todo) else {
+            return false;
+        };
+        let existing = self.pages[page_index].todos[todo_index].depends_on.contains(&dependency);
+        if !existing && depends_on_transitively(&self.pages, dependency, todo) {
+            return false;
+        }
+
+        let todo = &mut self.pages[page_index].todos[todo_index];
+        if existing {
+            todo.depends_on.retain(|id| *id != dependency);
+        } else {
+            todo.depends_on.push(dependency);
+        }
+        todo.touch();
+        true
+    }
+
+    fn focus_todo(&mut self, page_index: usize, todo_index: usize) {
+        self.current_page_index = page_index;
+        self.selected_todo_index = todo_index;
+    }
+
+    fn toggle_todo(&mut self, index: usize) {
+        let todo = &mut self.pages[self.current_page_index].todos[index];
+        if todo.completed || todo.recurrence.is_none() {
+            todo.set_completed(!todo.completed);
+        } else if let Some(next) = todo.complete_occurrence(&mut self.next_id) {
+            self.current_todos_mut().insert(index + 1, next);
+        }
+
+        if self.settings.sink_completed {
+            let todos = self.current_todos_mut();
+            let todo = todos.remove(index);
+            let completed = todo.completed;
+            // reopened todos go after the last open one
+            let position = if completed {
+                todos.len()
+            } else {
+                todos.iter().rposition(|todo| !todo.completed).map_or(0, |last| last + 1)
+            };
+            todos.insert(position, todo);
+            if !completed {
+                self.selected_todo_index = position;
+            } else if self.selected_todo_index == index {
+                self.selected_todo_index = index.min(self.current_todos().len() - 1);
+            }
+        }
+    }
+
+    // indices into the current page's todos in the order they are shown
+    fn display_order(&self) -> Vec<usize> {
+        let todos = self.current_todos();
+        let mut order: Vec<usize> = (0..todos.len())
+            .filter(|&i| !(self.settings.hide_completed && todos[i].completed))
+            .collect();
+        match self.current_page().sort {
+            SortMode::Manual => {}
+            SortMode::Alpha => order.sort_by_key(|&i| todos[i].name.to_lowercase()),
+            SortMode::OpenFirst => order.sort_by_key(|&i| todos[i].completed),
+            SortMode::Due => order.sort_by_key(|&i| (todos[i].due.is_none(), todos[i].due)),
+            SortMode::Priority => order.sort_by_key(|&i| std::cmp::Reverse(todos[i].priority)),
+            SortMode::Created => order.sort_by_key(|&i| std::cmp::Reverse(todos[i].created_at)),
+        }
+        order
+    }
+
+    fn has_selection(&self) -> bool {
+        self.display_order().contains(&self.selected_todo_index)
+    }
+
+    // moves the selection off a hidden todo onto the next shown one, or the last if there is none.
+    // left alone while a prompt is open, since the prompt acts on the selected todo and the
+    // agenda, board and filter view can open one on a todo the list hides
+    fn clamp_selection(&mut self) {
+        if self.is_renaming_todo || self.is_setting_due || self.is_setting_recurrence || self.is_setting_estimate {
+            return;
+        }
+        let order = self.display_order();
+        if order.is_empty() || order.contains(&self.selected_todo_index) {
+            return;
+        }
+        self.selected_todo_index = order
+            .iter()
+            .copied()
+            .filter(|&i| i > self.selected_todo_index)
+            .min()
+            .unwrap_or(order[order.len() - 1]);
+    }
+
+    // moves the selection to the next or previous todo as displayed, wrapping around
+    fn move_selection(&mut self, forward: bool) {
+        let order = self.display_order();
+        if order.is_empty() {
+            return;
+        }
+        let position = order.iter().position(|&i| i == self.selected_todo_index).unwrap_or(0);
+        let next = if forward {
+            (position + 1) % order.len()
+        } else {
+            (position + order.len() - 1) % order.len()
+        };
+        self.selected_todo_index = order[next];
+    }
+
+    // swaps the selected todo with the one shown after or before it, wrapping around, so
+    // hidden completed todos are stepped over. returns whether anything moved
+    fn move_todo(&mut self, forward: bool) -> bool {
+        let order = self.display_order();
+        let Some(position) = order.iter().position(|&i| i == self.selected_todo_index) else {
+            return false;
+        };
+        if order.len() < 2 {
+            return false;
+        }
+        let other = if forward {
+            order[(position + 1) % order.len()]
+        } else {
+            order[(position + order.len() - 1) % order.len()]
+        };
+        let current = self.selected_todo_index;
+        self.current_todos_mut().swap(current, other);
+        self.selected_todo_index = other;
+        true
+    }
+
+    // (page index, todo index) of the todo whose timer is running
+    fn active_timer(&self) -> Option<(usize, usize)> {
+        self.pages.iter().enumerate().find_map(|(page_index, page)| {
+            page.todos
+                .iter()
+                .position(Todo::is_timing)
+                .map(|todo_index| (page_index, todo_index))
+        })
+    }
+
+    // starts timing the todo, stopping any other running timer, or stops it if it was running
+    fn toggle_timer(&mut self, index: usize) {
+        if self.current_todos()[index].is_timing() {
+            self.current_todos_mut()[index].stop_timer();
+            return;
+        }
+        self.start_timer(self.current_page_index, index);
+    }
+
+    // only one timer runs at a time, so any other is stopped first
+    fn start_timer(&mut self, page_index: usize, todo_index: usize) {
+        if let Some((active_page, active_todo)) = self.active_timer() {
+            self.pages[active_page].todos[active_todo].stop_timer();
+        }
+        self.pages[page_index].todos[todo_index].start_timer();
+    }
+
+    fn pomodoro_todo_mut(&mut self) -> Option<&mut Todo> {
+        let (page_index, todo_index) = find_todo(&self.pages, self.pomodoro.as_ref()?.todo)?;
+        Some(&mut self.pages[page_index].todos[todo_index])
+    }
+
+    fn start_pomodoro(&mut self, index: usize) {
+        self.stop_pomodoro();
+
+        let now = Local::now();
+        self.pomodoro = Some(Pomodoro {
+            todo: self.current_todos()[index].id,
+            phase: PomodoroPhase::Work,
+            started_at: now,
+            ends_at: now + chrono::Duration::minutes(self.settings.pomodoro_work_minutes.max(1)),
+        });
+        if !self.current_todos()[index].is_timing() {
+            self.toggle_timer(index);
+        }
+    }
+
+    fn stop_pomodoro(&mut self) {
+        if let Some(todo) = self.pomodoro_todo_mut() {
+            todo.stop_timer();
+        }
+        self.pomodoro = None;
+    }
+
+    fn on_tick(&mut self) {
+        let now = Local::now();
+        let Some(pomodoro) = &self.pomodoro else {
+            return;
+        };
+        if now < pomodoro.ends_at {
+            return;
+        }
+
+        let finished = pomodoro.phase;
+        let (phase, minutes) = match finished {
+            PomodoroPhase::Work => (PomodoroPhase::Break, self.settings.pomodoro_break_minutes),
+            PomodoroPhase::Break => (PomodoroPhase::Work, self.settings.pomodoro_work_minutes),
+        };
+
+        let Some((page_index, todo_index)) = find_todo(&self.pages, pomodoro.todo) else {
+            self.pomodoro = None;
+            return;
+        };
+        let todo = &mut self.pages[page_index].todos[todo_index];
+        if finished == PomodoroPhase::Work {
+            todo.pomodoros += 1;
+            todo.stop_timer();
+        } else if !todo.completed {
+            self.start_timer(page_index, todo_index);
+        }
+
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.phase = phase;
+            pomodoro.started_at = now;
+            pomodoro.ends_at = now + chrono::Duration::minutes(minutes.max(1));
+        }
+
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x07").ok();
+        stdout.flush().ok();
+        self.save_app_data().ok();
+    }
+
+    fn trash_todo(&mut self, index: usize) {
+        let mut todo = self.current_todos_mut().remove(index);
+        todo.stop_timer();
+        self.trash.push(TrashEntry {
+            item: TrashedItem::Todo {
+                page: self.current_page().name.clone(),
+                page_id: self.current_page().id,
+                position: index,
+                todo: Box::new(todo),
+            },
+            deleted_at: Local::now(),
+        });
+
+        if self.current_todos().is_empty() {
+            self.selected_todo_index = 0;
+        } else if self.selected_todo_index >= self.current_todos().len() {
+            self.selected_todo_index = self.current_todos().len() - 1;
+        }
+    }
+
+    fn trash_page(&mut self, index: usize) {
+        let mut page = self.pages.remove(index);
+        for todo in &mut page.todos {
+            todo.stop_timer();
+        }
+        self.trash.push(TrashEntry {
+            item: TrashedItem::Page { position: index, page },
+            deleted_at: Local::now(),
+        });
+
+        if self.current_page_index >= self.pages.len() {
+            self.current_page_index = self.pages.len() - 1;
+        }
+        self.selected_todo_index = 0;
+    }
+
+    fn restore_trash_entry(&mut self, index: usize) {
+        let entry = self.trash.remove(index);
+        match entry.item {
+            TrashedItem::Todo { page, page_id, position, todo } => {
+                let found = match page_id {
+                    0 => self.pages.iter().position(|p| p.name == page),
+                    id => self.pages.iter().position(|p| p.id == id),
+                };
+                let page_index = match found {
+                    Some(i) => i,
+                    None => {
+                        self.pages.push(Page::new(take_id(&mut self.next_id), page));
+                        self.pages.len() - 1
+                    }
+                };
+                let todos = &mut self.pages[page_index].todos;
+                let position = position.min(todos.len());
+                todos.insert(position, *todo);
+                self.current_page_index = page_index;
+                self.selected_todo_index = position;
+            }
+            TrashedItem::Page { position, page } => {
+                let position = position.min(self.pages.len());
+                self.pages.insert(position, page);
+                self.current_page_index = position;
+                self.selected_todo_index = 0;
+            }
+        }
+
+        if self.selected_trash_index >= self.trash.len() {
+            self.selected_trash_index = self.trash.len().saturating_sub(1);
+        }
+    }
+
+    // replaces a page's todos with an edited checklist, keeping the data of todos whose
+    // names are unchanged and sending todos missing from the checklist to the trash
+    fn apply_checklist(&mut self, page_index: usize, title: Option<String>, items: Vec<markdown::ChecklistItem>) {
+        let page_name = self.pages[page_index].name.clone();
+        let page_id = self.pages[page_index].id;
+        let mut old_todos: Vec<Option<(usize, Todo)>> = std::mem::take(&mut self.pages[page_index].todos)
+            .into_iter()
+            .enumerate()
+            .map(Some)
+            .collect();
+
+        let mut todos = Vec::with_capacity(items.len());
+        for item in items {
+            let existing = old_todos
+                .iter_mut()
+                .find(|slot| matches!(slot, Some((_, todo)) if todo.name.trim() == item.name))
+                .and_then(Option::take);
+            let mut todo = match existing {
+                Some((_, todo)) => todo,
+                None => Todo::new(take_id(&mut self.next_id), item.name.clone()),
+            };
+            if todo.name != item.name || todo.notes != item.notes {
+                todo.name = item.name;
+                todo.notes = item.notes;
+                todo.touch();
+            }
+            // ticking a recurring todo schedules its next occurrence, as toggling it does
+            let next = if item.completed && !todo.completed && todo.recurrence.is_some() {
+                todo.complete_occurrence(&mut self.next_id)
+            } else {
+                todo.set_completed(item.completed);
+                None
+            };
+            todos.push(todo);
+            todos.extend(next);
+        }
+
+        for (position, mut todo) in old_todos.into_iter().flatten() {
+            todo.stop_timer();
+            self.trash.push(TrashEntry {
+                item: TrashedItem::Todo {
+                    page: page_name.clone(),
+                    page_id,
+                    position,
+                    todo: Box::new(todo),
+                },
+                deleted_at: Local::now(),
+            });
+        }
+
+        let page = &mut self.pages[page_index];
+        page.todos = todos;
+        if let Some(title) = title.filter(|title| !title.is_empty()) {
+            page.name = title;
+        }
+
+        if page.todos.is_empty() {
+            self.selected_todo_index = 0;
+        } else if self.selected_todo_index >= page.todos.len() {
+            self.selected_todo_index = page.todos.len() - 1;
+        }
+    }
+
+    fn purge_trash_entry(&mut self, index: usize) {
+        self.trash.remove(index);
+        if self.selected_trash_index >= self.trash.len() {
+            self.selected_trash_index = self.trash.len().saturating_sub(1);
+        }
+    }
+
+    // page indices matching the picker input, best match first
+    fn picker_matches(&self) -> Vec<usize> {
+        let mut scored: Vec<(i32, usize)> = self
+            .pages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, page)| fuzzy_score(&self.page_picker_input, &page.name).map(|score| (score, i)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    // whether the picker offers to create a page named after its input
+    fn picker_can_create(&self) -> bool {
+        !self.page_picker_input.is_empty() && !self.pages.iter().any(|p| p.name == self.page_picker_input)
+    }
+
+    fn picker_len(&self) -> usize {
+        self.picker_matches().len() + usize::from(self.picker_can_create())
+    }
+
+    fn needs_confirmation(&self, action: PendingAction) -> bool {
+        match action {
+            PendingAction::ToggleTodo(index) => {
+                let todo = &self.current_todos()[index];
+                self.settings.confirm_complete_blocked && !todo.completed && !self.blockers(todo).is_empty()
+            }
+            PendingAction::DeleteTodo(_) => self.settings.confirm_delete_todo,
+            PendingAction::DeletePage(_) | PendingAction::DeleteView(_) => self.settings.confirm_delete_page,
+            PendingAction::PurgeTrashEntry(_) | PendingAction::PurgeTrash => self.settings.confirm_purge,
+        }
+    }
+
+    fn request_action(&mut self, action: PendingAction) {
+        if self.needs_confirmation(action) {
+            self.pending_action = Some(action);
+        } else {
+            self.run_action(action);
+        }
+    }
+
+    fn run_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::ToggleTodo(index) => self.toggle_todo(index),
+            PendingAction::DeleteTodo(index) => self.trash_todo(index),
+            PendingAction::DeletePage(index) => self.trash_page(index),
+            PendingAction::DeleteView(index) => {
+                self.views.remove(index);
+                self.current_view = None;
+                self.view = View::Todos;
+            }
+            PendingAction::PurgeTrashEntry(index) => self.purge_trash_entry(index),
+            PendingAction::PurgeTrash => {
+                self.trash.clear();
+                self.selected_trash_index = 0;
+            }
+        }
+        self.save_app_data().ok();
+    }
+
+    fn describe_action(&self, action: PendingAction) -> String {
+        match action {
+            PendingAction::ToggleTodo(index) => {
+                let todo = &self.current_todos()[index];
+                let blockers: Vec<String> = self.blockers(todo).iter().map(|todo| format!("'{}'", todo.name)).collect();
+                format!("'{}' is blocked by {}. complete it anyway?", todo.name, blockers.join(", "))
+            }
+            PendingAction::DeleteTodo(index) => {
+                format!("delete todo '{}'?", self.current_todos()[index].name)
+            }
+            PendingAction::DeletePage(index) => {
+                let page = &self.pages[index];
+                format!("delete page '{}' with {} todos?", page.name, page.todos.len())
+            }
+            PendingAction::DeleteView(index) => {
+                format!("delete view '{}'? its todos are kept", self.views[index].name)
+            }
+            PendingAction::PurgeTrashEntry(index) => {
+                let name = match &self.trash[index].item {
+                    TrashedItem::Todo { todo, .. } => &todo.name,
+                    TrashedItem::Page { page, .. } => &page.name,
+                };
+                format!("permanently purge '{}'?", name)
+            }
+            PendingAction::PurgeTrash => {
+                format!("permanently purge all {} items in the trash?", self.trash.len())
+            }
+        }
+    }
+
+    // returns whether anything was purged
+    fn purge_expired_trash(&mut self) -> bool {
+        if self.settings.trash_retention_days <= 0 {
+            return false;
+        }
+        let cutoff = Local::now() - chrono::Duration::days(self.settings.trash_retention_days);
+        let before = self.trash.len();
+        self.trash.retain(|entry| entry.deleted_at > cutoff);
+        self.trash.len() != before
+    }
+
+    // item_heights holds the number of wrapped lines of each todo as displayed
+    fn update_scrollbar(&mut self, list_height: usize, item_heights: &[usize], selected: usize) {
+        if !item_heights.is_empty() {
+            let selected = selected.min(item_heights.len() - 1);
+            self.scrollbar_state = ScrollbarState::default()
+                .content_length(item_heights.iter().sum())
+                .viewport_content_length(list_height)
+                .position(item_heights[..selected].iter().sum());
+        }
+    }
+
+    fn process_input_event(&mut self, key: KeyEvent) -> bool {
+        if let Some(action) = self.pending_action {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.pending_action = None;
+                    self.run_action(action);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.pending_action = None;
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.is_creating_todo {
+            match key.code {
+                KeyCode::Down => {
+                    self.move_selection(true);
+                    return true;
+                }
+                KeyCode::Up => {
+                    self.move_selection(false);
+                    return true;
+                }
+                _ => {}
+            }
+
+            match Self::edit_buffer(&mut self.new_todo_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let input: String = self.new_todo_input.drain(..).collect();
+                    let (name, estimate) = split_estimate(&input);
+                    if !name.is_empty() {
+                        let mut todo = Todo::new(take_id(&mut self.next_id), name);
+                        todo.estimate = estimate;
+                        self.current_todos_mut().push(todo);
+                        self.selected_todo_index = self.current_todos().len() - 1;
+                        self.save_app_data().ok();
+                    }
+                    self.is_creating_todo = false;
+                    self.cursor_position = 0;
+                }
+                EditResult::Esc => {
+                    self.is_creating_todo = false;
+                    self.new_todo_input.clear();
+                    self.cursor_position = 0;
+                }
+                EditResult::None => {}
+            }
+
+            return true;
+        }
+
+        if self.is_creating_page {
+            match Self::edit_buffer(&mut self.new_page_name_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let page_name = if self.new_page_name_input.is_empty() {
+                        "".to_string()
+                    } else {
+                        self.new_page_name_input.drain(..).collect()
+                    };
+                    self.pages.push(Page::new(take_id(&mut self.next_id), page_name));
+                    self.current_page_index = self.pages.len() - 1;
+                    self.selected_todo_index = 0;
+                    self.is_creating_page = false;
+                    self.cursor_position = 0;
+                    self.save_app_data().ok();
+                }
+                EditResult::Esc => {
+                    self.is_creating_page = false;
+                    self.new_page_name_input.clear();
+                    self.cursor_position = 0;
+                }
+                EditResult::None => {}
+            }
+            return true;
+        }
+
+        if self.is_renaming_page {
+            match Self::edit_buffer(&mut self.rename_page_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    if self.rename_page_input.is_empty() {
+                        if self.pages.len() > 1 {
+                            self.request_action(PendingAction::DeletePage(self.current_page_index));
+                        }
+                    } else {
+                        self.pages[self.current_page_index].name = self.rename_page_input.drain(..).collect();
+                        self.save_app_data().ok();
+                    }
+                    self.is_renaming_page = false;
+                    self.rename_page_input.clear();
+                }
+                EditResult::Esc => {
+                    self.is_renaming_page = false;
+                    self.rename_page_input.clear();
+                }
+                EditResult::None => {}
+            }
+            return true;
+        }
+
+        if self.is_renaming_todo {
             match Self::edit_buffer(&mut self.rename_todo_input, &mut self.cursor_position, key) {
                 EditResult::Enter => {
                     if !self.current_todos().is_empty() {
                         let index = self.selected_todo_index;
                         if self.rename_todo_input.is_empty() {
-                            self.current_todos_mut().remove(index);
-                            if self.current_todos().is_empty() {
-                                self.selected_todo_index = 0;
-                            } else if self.selected_todo_index >= self.current_todos().len() {
-                                self.selected_todo_index = self.current_todos().len() - 1;
-                            }
+                            self.request_action(PendingAction::DeleteTodo(index));
                         } else {
-                            self.current_todos_mut()[index].name = self.rename_todo_input.drain(..).collect();
+                            let name = self.rename_todo_input.drain(..).collect();
+                            let todo = &mut self.current_todos_mut()[index];
+                            todo.name = name;
+                            todo.touch();
+                            self.save_app_data().ok();
                         }
-                        self.save_app_data().ok();
                     }
                     self.is_renaming_todo = false;
                     self.rename_todo_input.clear();
@@ -251,455 +1482,2312 @@ impl App {
             return true;
         }
 
-        false
+        if self.is_picking_page {
+            match key.code {
+                KeyCode::Down => {
+                    let len = self.picker_len();
+                    if len > 0 {
+                        self.selected_picker_index = (self.selected_picker_index + 1) % len;
+                    }
+                    return true;
+                }
+                KeyCode::Up => {
+                    let len = self.picker_len();
+                    if len > 0 {
+                        self.selected_picker_index = (self.selected_picker_index + len - 1) % len;
+                    }
+                    return true;
+                }
+                _ => {}
+            }
+
+            match Self::edit_buffer(&mut self.page_picker_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let matches = self.picker_matches();
+                    if let Some(&page_index) = matches.get(self.selected_picker_index) {
+                        self.current_page_index = page_index;
+                        self.selected_todo_index = 0;
+                    } else if self.picker_can_create() {
+                        let name = self.page_picker_input.clone();
+                        self.pages.push(Page::new(take_id(&mut self.next_id), name));
+                        self.current_page_index = self.pages.len() - 1;
+                        self.selected_todo_index = 0;
+                        self.save_app_data().ok();
+                    }
+                    self.is_picking_page = false;
+                    self.page_picker_input.clear();
+                    self.cursor_position = 0;
+                }
+                EditResult::Esc => {
+                    self.is_picking_page = false;
+                    self.page_picker_input.clear();
+                    self.cursor_position = 0;
+                }
+                EditResult::None => {
+                    self.selected_picker_index = 0;
+                }
+            }
+            return true;
+        }
+
+        if self.is_setting_due {
+            match Self::edit_buffer(&mut self.due_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let today = Local::now().date_naive();
+                    let due = if self.due_input.trim().is_empty() {
+                        Some(None)
+                    } else {
+                        dates::parse_date(&self.due_input, today).map(Some)
+                    };
+                    match due {
+                        Some(due) => {
+                            let index = self.selected_todo_index;
+                            let todo = &mut self.current_todos_mut()[index];
+                            todo.due = due;
+                            todo.touch();
+                            self.save_app_data().ok();
+                            self.is_setting_due = false;
+                            self.due_input.clear();
+                            self.input_error = false;
+                        }
+                        None => self.input_error = true,
+                    }
+                }
+                EditResult::Esc => {
+                    self.is_setting_due = false;
+                    self.due_input.clear();
+                    self.input_error = false;
+                }
+                EditResult::None => self.input_error = false,
+            }
+            return true;
+        }
+
+        if self.is_setting_recurrence {
+            match Self::edit_buffer(&mut self.recurrence_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let recurrence = if self.recurrence_input.trim().is_empty() {
+                        Some(None)
+                    } else {
+                        Recurrence::parse(&self.recurrence_input).map(Some)
+                    };
+                    match recurrence {
+                        Some(recurrence) => {
+                            let index = self.selected_todo_index;
+                            let todo = &mut self.current_todos_mut()[index];
+                            todo.recurrence = recurrence;
+                            todo.touch();
+                            self.save_app_data().ok();
+                            self.is_setting_recurrence = false;
+                            self.recurrence_input.clear();
+                            self.input_error = false;
+                        }
+                        None => self.input_error = true,
+                    }
+                }
+                EditResult::Esc => {
+                    self.is_setting_recurrence = false;
+                    self.recurrence_input.clear();
+                    self.input_error = false;
+                }
+                EditResult::None => self.input_error = false,
+            }
+            return true;
+        }
+
+        if self.is_setting_estimate {
+            match Self::edit_buffer(&mut self.estimate_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let estimate = if self.estimate_input.trim().is_empty() {
+                        Some(None)
+                    } else {
+                        parse_estimate(&self.estimate_input).map(Some)
+                    };
+                    match estimate {
+                        Some(estimate) => {
+                            let index = self.selected_todo_index;
+                            let todo = &mut self.current_todos_mut()[index];
+                            todo.estimate = estimate;
+                            todo.touch();
+                            self.save_app_data().ok();
+                            self.is_setting_estimate = false;
+                            self.estimate_input.clear();
+                            self.input_error = false;
+                        }
+                        None => self.input_error = true,
+                    }
+                }
+                EditResult::Esc => {
+                    self.is_setting_estimate = false;
+                    self.estimate_input.clear();
+                    self.input_error = false;
+                }
+                EditResult::None => self.input_error = false,
+            }
+            return true;
+        }
+
+        if self.is_filtering {
+            match Self::edit_buffer(&mut self.filter_input, &mut self.cursor_position, key) {
+                EditResult::Enter => match Filter::parse(&self.filter_input) {
+                    Ok(filter) => {
+                        self.filter = Some(filter);
+                        self.filter_query = self.filter_input.drain(..).collect();
+                        self.selected_filter_index = 0;
+                        self.view = View::Filter;
+                        self.is_filtering = false;
+                        self.input_error = false;
+                    }
+                    Err(_) => self.input_error = true,
+                },
+                EditResult::Esc => {
+                    self.is_filtering = false;
+                    self.filter_input.clear();
+                    self.input_error = false;
+                }
+                EditResult::None => self.input_error = false,
+            }
+            return true;
+        }
+
+        if self.is_naming_view {
+            match Self::edit_buffer(&mut self.view_name_input, &mut self.cursor_position, key) {
+                EditResult::Enter => {
+                    let name = self.view_name_input.trim().to_string();
+                    if !name.is_empty() {
+                        // saving under an existing name updates that view
+                        let index = match self.views.iter().position(|view| view.name == name) {
+                            Some(index) => index,
+                            None => {
+                                self.views.push(SavedView { name: name.clone(), filter: String::new() });
+                                self.views.len() - 1
+                            }
+                        };
+                        self.views[index].filter = self.filter_query.clone();
+                        self.current_view = Some(index);
+                        self.save_app_data().ok();
+                    }
+                    self.is_naming_view = false;
+                    self.view_name_input.clear();
+                }
+                EditResult::Esc => {
+                    self.is_naming_view = false;
+                    self.view_name_input.clear();
+                }
+                EditResult::None => {}
+            }
+            return true;
+        }
+
+        if self.view == View::Filter {
+            let entries = self.filter_entries();
+            let selected = entries.get(self.selected_filter_index.min(entries.len().saturating_sub(1)));
+            // only keys that act on the selected todo move the list to it
+            let acts_on_todo = matches!(key.code, KeyCode::Enter | KeyCode::Char('r' | 'D' | 'R' | '~' | 'e' | 'o'));
+            if let (Some(&(page_index, todo_index)), true) = (selected, acts_on_todo) {
+                self.focus_todo(page_index, todo_index);
+            }
+
+            match key.code {
+                KeyCode::Esc => {
+                    self.view = View::Todos;
+                    self.current_view = None;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                KeyCode::Char('/') => self.start_filtering(),
+                KeyCode::Char('s') if self.filter.is_some() => self.start_naming_view(),
+                KeyCode::Char('x') if self.current_view.is_some() => {
+                    if let Some(index) = self.current_view {
+                        self.request_action(PendingAction::DeleteView(index));
+                    }
+                }
+                KeyCode::Left | KeyCode::Char('h') => self.step_tab(false),
+                KeyCode::Right | KeyCode::Char('l') => self.step_tab(true),
+                KeyCode::Down | KeyCode::Char('j') if !entries.is_empty() => {
+                    self.selected_filter_index = (self.selected_filter_index + 1) % entries.len();
+                }
+                KeyCode::Up | KeyCode::Char('k') if !entries.is_empty() => {
+                    self.selected_filter_index = (self.selected_filter_index + entries.len() - 1) % entries.len();
+                }
+                KeyCode::Enter if selected.is_some() => {
+                    self.request_action(PendingAction::ToggleTodo(self.selected_todo_index));
+                }
+                KeyCode::Char('r') if selected.is_some() => self.start_renaming_todo(),
+                KeyCode::Char('D') if selected.is_some() => self.start_setting_due(),
+                KeyCode::Char('R') if selected.is_some() => self.start_setting_recurrence(),
+                KeyCode::Char('~') if selected.is_some() => self.start_setting_estimate(),
+                KeyCode::Char('e') if selected.is_some() => {
+                    self.editor_request = Some(EditorRequest::Notes(self.selected_todo_index));
+                }
+                KeyCode::Char('o') if selected.is_some() => {
+                    self.view = View::Todos;
+                    self.current_view = None;
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.view == View::Agenda {
+            let entries = self.agenda_entries();
+            let selected = entries.get(self.selected_agenda_index.min(entries.len().saturating_sub(1)));
+            if let Some(entry) = selected {
+                self.focus_todo(entry.page_index, entry.todo_index);
+            }
+
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('A') => {
+                    self.view = View::Todos;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                KeyCode::Down | KeyCode::Char('j') if !entries.is_empty() => {
+                    self.selected_agenda_index = (self.selected_agenda_index + 1) % entries.len();
+                }
+                KeyCode::Up | KeyCode::Char('k') if !entries.is_empty() => {
+                    self.selected_agenda_index = (self.selected_agenda_index + entries.len() - 1) % entries.len();
+                }
+                KeyCode::Enter if selected.is_some() => {
+                    self.request_action(PendingAction::ToggleTodo(self.selected_todo_index));
+                }
+                KeyCode::Char('r') if selected.is_some() => self.start_renaming_todo(),
+                KeyCode::Char('D') if selected.is_some() => self.start_setting_due(),
+                KeyCode::Char('R') if selected.is_some() => self.start_setting_recurrence(),
+                KeyCode::Char('~') if selected.is_some() => self.start_setting_estimate(),
+                KeyCode::Char('e') if selected.is_some() => {
+                    self.editor_request = Some(EditorRequest::Notes(self.selected_todo_index));
+                }
+                KeyCode::Char('o') if selected.is_some() => {
+                    self.view = View::Todos;
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.view == View::Stats {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('S') => {
+                    self.view = View::Todos;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.view == View::Calendar {
+            let shift = key.modifiers.contains(event::KeyModifiers::SHIFT);
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('C') => {
+                    self.view = View::Todos;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                KeyCode::Char('H') => {
+                    self.move_calendar(self.calendar_date.checked_sub_months(Months::new(1)));
+                }
+                KeyCode::Char('L') => {
+                    self.move_calendar(self.calendar_date.checked_add_months(Months::new(1)));
+                }
+                KeyCode::Left if shift => {
+                    self.move_calendar(self.calendar_date.checked_sub_months(Months::new(1)));
+                }
+                KeyCode::Right if shift => {
+                    self.move_calendar(self.calendar_date.checked_add_months(Months::new(1)));
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.move_calendar(self.calendar_date.checked_sub_days(Days::new(1)));
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    self.move_calendar(self.calendar_date.checked_add_days(Days::new(1)));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.move_calendar(self.calendar_date.checked_sub_days(Days::new(7)));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.move_calendar(self.calendar_date.checked_add_days(Days::new(7)));
+                }
+                KeyCode::Char('.') => {
+                    self.calendar_date = Local::now().date_naive();
+                }
+                KeyCode::Char('o') | KeyCode::Enter => {
+                    if let Some(&(page_index, todo_index)) = self.todos_due_on(self.calendar_date).first() {
+                        self.focus_todo(page_index, todo_index);
+                        self.view = View::Todos;
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.view == View::Board {
+            let cards = self.board_cards();
+            self.board_column_index = self.board_column_index.min(cards.len() - 1);
+            let column = &cards[self.board_column_index];
+            self.board_row_index = self.board_row_index.min(column.len().saturating_sub(1));
+            let selected = column.get(self.board_row_index).copied();
+            if let Some(index) = selected {
+                self.selected_todo_index = index;
+            }
+
+            let shift = key.modifiers.contains(event::KeyModifiers::SHIFT);
+            let target_column = match key.code {
+                KeyCode::Char('H') => Some(self.board_column_index.checked_sub(1)),
+                KeyCode::Left if shift => Some(self.board_column_index.checked_sub(1)),
+                KeyCode::Char('L') => Some(Some(self.board_column_index + 1).filter(|&c| c < cards.len())),
+                KeyCode::Right if shift => Some(Some(self.board_column_index + 1).filter(|&c| c < cards.len())),
+                _ => None,
+            };
+            if let Some(target_column) = target_column {
+                if let (Some(index), Some(target_column)) = (selected, target_column) {
+                    // the last column completes the card, which asks first like completing it from the list
+                    let action = PendingAction::ToggleTodo(index);
+                    if target_column == cards.len() - 1 && self.needs_confirmation(action) {
+                        self.pending_action = Some(action);
+                        return true;
+                    }
+                    self.move_to_board_column(index, target_column);
+                    self.board_column_index = target_column;
+                    self.board_row_index = self.board_cards()[target_column]
+                        .iter()
+                        .position(|&i| i == index)
+                        .unwrap_or(0);
+                    self.save_app_data().ok();
+                }
+                return true;
+            }
+
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('b') => {
+                    self.view = View::Todos;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.board_column_index = self.board_column_index.saturating_sub(1);
+                    self.board_row_index = 0;
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    self.board_column_index = (self.board_column_index + 1).min(cards.len() - 1);
+                    self.board_row_index = 0;
+                }
+                KeyCode::Down | KeyCode::Char('j') if !column.is_empty() => {
+                    self.board_row_index = (self.board_row_index + 1) % column.len();
+                }
+                KeyCode::Up | KeyCode::Char('k') if !column.is_empty() => {
+                    self.board_row_index = (self.board_row_index + column.len() - 1) % column.len();
+                }
+                KeyCode::Enter if selected.is_some() => {
+                    self.request_action(PendingAction::ToggleTodo(self.selected_todo_index));
+                }
+                KeyCode::Char('r') if selected.is_some() => self.start_renaming_todo(),
+                KeyCode::Char('d') if selected.is_some() => {
+                    self.request_action(PendingAction::DeleteTodo(self.selected_todo_index));
+                }
+                KeyCode::Char('e') if selected.is_some() => {
+                    self.editor_request = Some(EditorRequest::Notes(self.selected_todo_index));
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        if self.is_viewing_trash {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('t') => {
+                    self.is_viewing_trash = false;
+                }
+                KeyCode::Char('q') => {
+                    self.should_quit = true;
+                }
+                KeyCode::Down | KeyCode::Char('j') if !self.trash.is_empty() => {
+                    self.selected_trash_index = (self.selected_trash_index + 1) % self.trash.len();
+                }
+                KeyCode::Up | KeyCode::Char('k') if !self.trash.is_empty() => {
+                    self.selected_trash_index = (self.selected_trash_index + self.trash.len() - 1) % self.trash.len();
+                }
+                KeyCode::Enter | KeyCode::Char('u') if !self.trash.is_empty() => {
+                    self.restore_trash_entry(self.selected_trash_index);
+                    self.save_app_data().ok();
+                }
+                KeyCode::Char('d') if !self.trash.is_empty() => {
+                    self.request_action(PendingAction::PurgeTrashEntry(self.selected_trash_index));
+                }
+                KeyCode::Char('D') if !self.trash.is_empty() => {
+                    self.request_action(PendingAction::PurgeTrash);
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        // navigation keys fall through to the todo list while picking a dependency
+        if let Some(id) = self.linking_todo {
+            let shifted = key.modifiers.contains(event::KeyModifiers::SHIFT);
+            // digits may switch to another page, but not rename this one or start a new one
+            let switches_page = match key.code {
+                KeyCode::Char(c @ '1'..='9') => {
+                    let page_index = c as usize - '1' as usize;
+                    page_index < self.pages.len() && page_index != self.current_page_index
+                }
+                _ => false,
+            };
+            match key.code {
+                KeyCode::Enter => {
+                    let Some(dependency) = self.current_todos().get(self.selected_todo_index).map(|todo| todo.id) else {
+                        return true;
+                    };
+                    if dependency == id || !self.toggle_dependency(id, dependency) {
+                        self.input_error = true;
+                        return true;
+                    }
+                    if let Some((page_index, todo_index)) = find_todo(&self.pages, id) {
+                        self.focus_todo(page_index, todo_index);
+                    }
+                    self.save_app_data().ok();
+                    self.linking_todo = None;
+                    self.input_error = false;
+                }
+                KeyCode::Esc => {
+                    if let Some((page_index, todo_index)) = find_todo(&self.pages, id) {
+                        self.focus_todo(page_index, todo_index);
+                    }
+                    self.linking_todo = None;
+                    self.input_error = false;
+                }
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right if !shifted => {
+                    self.input_error = false;
+                    return false;
+                }
+                KeyCode::Char('h' | 'j' | 'k' | 'l' | 'p') => {
+                    self.input_error = false;
+                    return false;
+                }
+                KeyCode::Char('1'..='9') if switches_page => {
+                    self.input_error = false;
+                    return false;
+                }
+                _ => {}
+            }
+            return true;
+        }
+
+        false
+    }
+
+    fn edit_buffer(buf: &mut String, cursor_pos: &mut usize, key: KeyEvent) -> EditResult {
+        match key.code {
+            KeyCode::Enter => EditResult::Enter,
+            KeyCode::Esc => EditResult::Esc,
+            KeyCode::Char(c) => {
+                buf.insert(*cursor_pos, c);
+                *cursor_pos += 1;
+                EditResult::None
+            }
+            KeyCode::Backspace => {
+                if *cursor_pos > 0 {
+                    *cursor_pos -= 1;
+                    buf.remove(*cursor_pos);
+                }
+                EditResult::None
+            }
+            KeyCode::Left => {
+                if key.modifiers.contains(event::KeyModifiers::CONTROL) {
+                    if *cursor_pos > 0 {
+                        let bytes = buf.as_bytes();
+                        let mut pos = *cursor_pos - 1;
+                        while pos > 0 && bytes[pos].is_ascii_whitespace() { pos -= 1; }
+                        while pos > 0 && !bytes[pos].is_ascii_whitespace() { pos -= 1; }
+                        if pos > 0 || bytes[0].is_ascii_whitespace() { pos += 1; }
+                        *cursor_pos = pos;
+                    }
+                } else if *cursor_pos > 0 {
+                    *cursor_pos -= 1;
+                }
+                EditResult::None
+            }
+            KeyCode::Right => {
+                if key.modifiers.contains(event::KeyModifiers::CONTROL) {
+                    let len = buf.len();
+                    if *cursor_pos < len {
+                        let bytes = buf.as_bytes();
+                        let mut pos = *cursor_pos;
+                        while pos < len && !bytes[pos].is_ascii_whitespace() { pos += 1; }
+                        while pos < len && bytes[pos].is_ascii_whitespace() { pos += 1; }
+                        *cursor_pos = pos;
+                    }
+                } else if *cursor_pos < buf.len() {
+                    *cursor_pos += 1;
+                }
+                EditResult::None
+            }
+            _ => EditResult::None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum EditResult {
+    Enter,
+    Esc,
+    None,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = cli::run(&args) {
+            eprintln!("doodoo: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // loaded before the terminal is taken over so a broken data file can be reported
+    let mut app = match App::new() {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("doodoo: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    let res = run_app(&mut terminal, &mut app);
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        println!("{:?}", err)
+    }
+
+    Ok(())
+}
+
+fn run_app<B: ratatui::backend::Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+) -> io::Result<()> {
+    let tick_rate = Duration::from_millis(TICK_RATE_MS);
+    let mut last_tick = Instant::now();
+
+    loop {
+        terminal.draw(|f| ui(f, app))?;
+
+        if app.should_quit {
+            return Ok(());
+        }
+
+        let timeout = tick_rate
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                } else {
+                    if app.process_input_event(key) {
+                    } else {
+                        match key.code {
+                            KeyCode::Char('q') => {
+                                app.should_quit = true;
+                            }
+                            KeyCode::Char('n') => {
+                                app.is_creating_todo = true;
+                                app.cursor_position = app.new_todo_input.len();
+                            }
+                            KeyCode::Char('N') => {
+                                app.is_creating_page = true;
+                                app.new_page_name_input.clear();
+                                app.cursor_position = 0;
+                            }
+                            KeyCode::Char('p') => {
+                                app.is_picking_page = true;
+                                app.page_picker_input.clear();
+                                app.selected_picker_index = 0;
+                                app.cursor_position = 0;
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
+                                    // reordering only makes sense while the page is in manual order
+                                    if app.current_page().sort.is_manual() && app.move_todo(true) {
+                                        app.save_app_data().unwrap();
+                                    }
+                                } else {
+                                    app.move_selection(true);
+                                }
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
+                                    if app.current_page().sort.is_manual() && app.move_todo(false) {
+                                        app.save_app_data().unwrap();
+                                    }
+                                } else {
+                                    app.move_selection(false);
+                                }
+                            }
+                            KeyCode::Enter if app.has_selection() => {
+                                app.request_action(PendingAction::ToggleTodo(app.selected_todo_index));
+                            }
+                            KeyCode::Char('o') => {
+                                let page = app.current_page_index;
+                                app.pages[page].sort = app.pages[page].sort.next();
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('!') if app.has_selection() => {
+                                let index = app.selected_todo_index;
+                                let todo = &mut app.current_todos_mut()[index];
+                                todo.priority = Priority::cycle(todo.priority);
+                                todo.touch();
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('B') if app.has_selection() => {
+                                app.linking_todo = Some(app.current_todos()[app.selected_todo_index].id);
+                            }
+                            KeyCode::Char('D') if app.has_selection() => {
+                                app.start_setting_due();
+                            }
+                            KeyCode::Char('R') if app.has_selection() => {
+                                app.start_setting_recurrence();
+                            }
+                            KeyCode::Char('~') if app.has_selection() => {
+                                app.start_setting_estimate();
+                            }
+                            KeyCode::Char('d') if app.has_selection() => {
+                                app.request_action(PendingAction::DeleteTodo(app.selected_todo_index));
+                            }
+                            KeyCode::Char('r') if app.has_selection() => {
+                                app.start_renaming_todo();
+                            }
+                            KeyCode::Char('A') => {
+                                app.view = View::Agenda;
+                                app.selected_agenda_index = 0;
+                            }
+                            KeyCode::Char('b') => {
+                                app.view = View::Board;
+                                let columns = app.board_columns();
+                                match app.current_todos().get(app.selected_todo_index) {
+                                    Some(todo) => {
+                                        app.board_column_index = app.board_column_of(todo, &columns);
+                                        app.board_row_index = app.board_cards()[app.board_column_index]
+                                            .iter()
+                                            .position(|&i| i == app.selected_todo_index)
+                                            .unwrap_or(0);
+                                    }
+                                    None => {
+                                        app.board_column_index = 0;
+                                        app.board_row_index = 0;
+                                    }
+                                }
+                            }
+                            KeyCode::Char('T') if app.has_selection() => {
+                                app.toggle_timer(app.selected_todo_index);
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('P') if app.has_selection() => {
+                                let selected = app.current_todos()[app.selected_todo_index].id;
+                                if app.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.todo == selected) {
+                                    app.stop_pomodoro();
+                                } else {
+                                    app.start_pomodoro(app.selected_todo_index);
+                                }
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('S') => {
+                                app.view = View::Stats;
+                            }
+                            KeyCode::Char('/') => {
+                                app.start_filtering();
+                            }
+                            KeyCode::Char('C') => {
+                                app.view = View::Calendar;
+                                app.calendar_date = app.current_todos()
+                                    .get(app.selected_todo_index)
+                                    .and_then(|todo| todo.due)
+                                    .unwrap_or_else(|| Local::now().date_naive());
+                            }
+                            KeyCode::Char('s') => {
+                                app.settings.page_layout = match app.settings.page_layout {
+                                    PageLayout::Tabs => PageLayout::Sidebar,
+                                    PageLayout::Sidebar => PageLayout::Tabs,
+                                };
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('e') if app.has_selection() => {
+                                app.editor_request = Some(EditorRequest::Notes(app.selected_todo_index));
+                            }
+                            KeyCode::Char('E') => {
+                                app.editor_request = Some(EditorRequest::Page(app.current_page_index));
+                            }
+                            KeyCode::Char('a') => {
+                                app.settings.show_ages = !app.settings.show_ages;
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('c') => {
+                                app.settings.hide_completed = !app.settings.hide_completed;
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('i') => {
+                                app.settings.show_details = !app.settings.show_details;
+                                app.save_app_data().unwrap();
+                            }
+                            KeyCode::Char('t') => {
+                                app.is_viewing_trash = true;
+                                app.selected_trash_index = app.trash.len().saturating_sub(1);
+                            }
+                            KeyCode::Right | KeyCode::Char('l') => {
+                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
+                                    if app.pages.len() > 1 {
+                                        let current = app.current_page_index;
+                                        let next = (current + 1) % app.pages.len();
+                                        app.pages.swap(current, next);
+                                        app.current_page_index = next;
+                                        app.save_app_data().unwrap();
+                                    }
+                                } else {
+                                    app.step_tab(true);
+                                }
+                            }
+                            KeyCode::Left | KeyCode::Char('h') => {
+                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
+                                    if app.pages.len() > 1 {
+                                        let current = app.current_page_index;
+                                        let prev = (current + app.pages.len() - 1) % app.pages.len();
+                                        app.pages.swap(current, prev);
+                                        app.current_page_index = prev;
+                                        app.save_app_data().unwrap();
+                                    }
+                                } else {
+                                    app.step_tab(false);
+                                }
+                            }
+                            KeyCode::Char(c @ '1'..='9') => {
+                                if let Some(digit) = c.to_digit(10) {
+                                    let page_index = (digit - 1) as usize;
+                                    if page_index < app.pages.len() {
+                                        if page_index == app.current_page_index {
+                                            app.rename_page_input = app.current_page().name.clone();
+                                            app.cursor_position = app.rename_page_input.len();
+                                            app.is_renaming_page = true;
+                                        } else {
+                                            app.current_page_index = page_index;
+                                            app.selected_todo_index = 0;
+                                        }
+                                    } else {
+                                        app.is_creating_page = true;
+                                        app.new_page_name_input.clear();
+                                        app.cursor_position = 0;
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(request) = app.editor_request.take() {
+            handle_editor_request(terminal, app, request)?;
+        }
+        // before the next draw so the list and the detail pane agree on a visible todo
+        app.clamp_selection();
+
+        if last_tick.elapsed() >= tick_rate {
+            app.on_tick();
+            last_tick = Instant::now();
+        }
+    }
+}
+
+fn handle_editor_request<B: ratatui::backend::Backend + Write>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    request: EditorRequest,
+) -> io::Result<()> {
+    match request {
+        EditorRequest::Notes(index) => {
+            let notes = app.current_todos()[index].notes.clone();
+            if let Some(edited) = edit_in_external_editor(terminal, &notes, "md")? {
+                let edited = edited.trim_end();
+                let todo = &mut app.current_todos_mut()[index];
+                if todo.notes != edited {
+                    todo.notes = edited.to_string();
+                    todo.touch();
+                }
+                app.save_app_data().ok();
+            }
+        }
+        EditorRequest::Page(index) => {
+            let checklist = markdown::page_to_checklist(&app.pages[index]);
+            if let Some(edited) = edit_in_external_editor(terminal, &checklist, "md")? {
+                if edited != checklist {
+                    let (title, items) = markdown::parse_checklist(&edited);
+                    app.apply_checklist(index, title, items);
+                    app.save_app_data().ok();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// suspends the tui while $VISUAL / $EDITOR edits a temp file, returning its new contents
+// or None if the editor exited unsuccessfully
+fn edit_in_external_editor<B: ratatui::backend::Backend + Write>(
+    terminal: &mut Terminal<B>,
+    contents: &str,
+    extension: &str,
+) -> io::Result<Option<String>> {
+    let (temp, mut file) = create_temp_file(extension)?;
+    file.write_all(contents.as_bytes())?;
+    drop(file);
+    let path = &temp.0;
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+
+    let status = Command::new(program).args(parts).arg(path).status();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+
+    let edited = match status {
+        Ok(status) if status.success() => Some(fs::read_to_string(path)?),
+        _ => None,
+    };
+
+    Ok(edited)
+}
+
+// removed when dropped, so the file goes away however the edit ends
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        fs::remove_file(&self.0).ok();
+    }
+}
+
+// a fresh file only we can read, with a random name so other users can't guess it
+// and put their own file or a symlink there first
+fn create_temp_file(extension: &str) -> io::Result<(TempFile, File)> {
+    for _ in 0..16 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.write_u128(Local::now().timestamp_nanos_opt().unwrap_or_default() as u128);
+        let path = env::temp_dir().join(format!("doodoo-{:016x}.{}", hasher.finish(), extension));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((TempFile(path), file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "couldn't create a temp file to edit"))
+}
+
+// the monday the calendar for a date's month starts on and the month's last day,
+// or None near the ends of chrono's range where the grid can't be drawn
+fn calendar_grid(date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let month_start = date.with_day(1)?;
+    let month_end = month_start.checked_add_months(Months::new(1))?.pred_opt()?;
+    let grid_start = month_start.checked_sub_days(Days::new(month_start.weekday().num_days_from_monday() as u64))?;
+    // the last row runs to the sunday after the month ends
+    month_end.checked_add_days(Days::new(6))?;
+    Some((grid_start, month_end))
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let is_in_input_mode = app.is_creating_todo
+        || app.is_creating_page
+        || app.is_renaming_page
+        || app.is_renaming_todo
+        || app.is_setting_due
+        || app.is_setting_recurrence
+        || app.is_setting_estimate
+        || app.is_filtering
+        || app.is_naming_view;
+    
+    let top_needed: u16 = if is_in_input_mode { 3 } else { 0 };
+
+    let list_min_height: u16 = 5;
+
+    let constraints = if top_needed > 0 {
+        vec![
+            Constraint::Length(top_needed),
+            Constraint::Min(list_min_height),
+        ]   
+    } else {
+        vec![Constraint::Min(list_min_height)]
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(&constraints)
+        .split(f.area());
+
+    let (top_chunk_opt, main_chunk) = if top_needed > 0 {
+        (Some(chunks[0]), chunks[1])
+    } else {
+        (None, chunks[0])
+    };
+
+    let neon_orange = Color::Rgb(255, 140, 0);
+    let bright_orange = Color::Rgb(255, 165, 0);
+    let dark_orange = Color::Rgb(180, 82, 0);
+    let selected_style = Style::default().fg(Color::White);
+    let done_style = Style::default().fg(dark_orange);
+    let default_style = Style::default().fg(bright_orange);
+    let preview_style = Style::default().fg(Color::Rgb(100, 100, 100));
+    let blocked_style = Style::default().fg(bright_orange).add_modifier(Modifier::DIM);
+    let todo_border_style = Style::default().fg(neon_orange);
+    let input_border_style = Style::default().fg(bright_orange);
+    let page_active_style = Style::default().fg(Color::Black).bg(neon_orange);
+    let page_inactive_style = Style::default().fg(bright_orange);
+
+    let main_chunk = match &app.pomodoro {
+        Some(pomodoro) if main_chunk.height > 6 => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(5), Constraint::Length(1)])
+                .split(main_chunk);
+
+            let now = Local::now();
+            let total = (pomodoro.ends_at - pomodoro.started_at).num_seconds().max(1);
+            let remaining = (pomodoro.ends_at - now).num_seconds().clamp(0, total);
+            let phase = match pomodoro.phase {
+                PomodoroPhase::Work => "focus",
+                PomodoroPhase::Break => "break",
+            };
+            let gauge = LineGauge::default()
+                .ratio(1.0 - remaining as f64 / total as f64)
+                .label(format!(
+                    " {} {} · {} ",
+                    phase,
+                    format_clock(chrono::Duration::seconds(remaining)),
+                    app.todo_by_id(pomodoro.todo).map(|todo| todo.name.as_str()).unwrap_or_default()
+                ))
+                .style(default_style)
+                .filled_style(Style::default().fg(neon_orange))
+                .unfilled_style(done_style)
+                .line_set(ratatui::symbols::line::THICK);
+            f.render_widget(gauge, rows[1]);
+
+            rows[0]
+        }
+        _ => main_chunk,
+    };
+
+    let list_height = (main_chunk.height.saturating_sub(2)) as usize;
+
+    if app.is_viewing_trash {
+        let now = Local::now();
+        let items: Vec<ListItem> = app
+            .trash
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let description = match &entry.item {
+                    TrashedItem::Todo { page, todo, .. } => format!("todo: {} (from {})", todo.name, page),
+                    TrashedItem::Page { page, .. } => format!("page: {} ({} todos)", page.name, page.todos.len()),
+                };
+                let selector = if i == app.selected_trash_index { ">> " } else { "   " };
+                let style = if i == app.selected_trash_index { selected_style } else { default_style };
+                ListItem::new(format!("{}{:>4} ago  {}", selector, format_age(now - entry.deleted_at), description)).style(style)
+            })
+            .collect();
+
+        let retention = if app.settings.trash_retention_days > 0 {
+            format!("purged after {}d", app.settings.trash_retention_days)
+        } else {
+            "kept forever".to_string()
+        };
+        let trash_title = Line::from(vec![
+            Span::styled(format!(" {} ", app.context_prefix), Style::default().fg(neon_orange)),
+            Span::styled(format!(" trash ({}) ", retention), page_active_style),
+        ]);
+        let help_text = " restore: [↵] | purge: [d] | purge all: [D] | nav: [↑↓],[jk] | back: [t] | quit: [q] ";
+
+        let list = List::new(items).block(
+            Block::default()
+                .title_top(trash_title)
+                .title_bottom(help_text)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+
+        let mut state = ListState::default();
+        if !app.trash.is_empty() {
+            state.select(Some(app.selected_trash_index));
+        }
+        f.render_stateful_widget(list, main_chunk, &mut state);
+        render_confirm_popup(f, app, default_style, input_border_style);
+        return;
+    }
+
+    if app.view == View::Agenda {
+        let today = Local::now().date_naive();
+        let entries = app.agenda_entries();
+        let selected = app.selected_agenda_index.min(entries.len().saturating_sub(1));
+        let header_style = Style::default().fg(neon_orange).add_modifier(ratatui::style::Modifier::BOLD);
+
+        let mut items: Vec<ListItem> = vec![];
+        let mut selected_row = None;
+        let mut previous_group = None;
+        for (i, entry) in entries.iter().enumerate() {
+            if previous_group != Some(entry.group) {
+                if previous_group.is_some() {
+                    items.push(ListItem::new(""));
+                }
+                items.push(ListItem::new(format!(" {}", entry.group.label())).style(header_style));
+                previous_group = Some(entry.group);
+            }
+
+            let todo = &app.pages[entry.page_index].todos[entry.todo_index];
+            let checkbox = if todo.completed { "[X] " } else { "[ ] " };
+            let selector = if i == selected { ">> " } else { "   " };
+            let style = if i == selected {
+                selected_style
+            } else if todo.completed {
+                done_style
+            } else {
+                default_style
+            };
+
+            let mut label = todo.name.clone();
+            if let Some(due) = todo.due {
+                label.push_str(&format!("  [{}]", dates::format_due(due, today)));
+            }
+            if todo.recurrence.is_some() {
+                label.push_str(" ↻");
+            }
+            label.push_str(&format!("  · {}", app.pages[entry.page_index].name));
+
+            if i == selected {
+                selected_row = Some(items.len());
+            }
+            items.push(ListItem::new(wrapped_item(&format!("{}{}", selector, checkbox), &label, (main_chunk.width as usize).saturating_sub(2))).style(style));
+        }
+
+        let agenda_title = Line::from(vec![
+            Span::styled(format!(" {} ", app.context_prefix), Style::default().fg(neon_orange)),
+            Span::styled(" agenda ", page_active_style),
+        ]);
+        let help_text = " complete: [↵] | rename: [r] | due: [D] | repeat: [R] | estimate: [~] | notes: [e] | open page: [o] | nav: [↑↓],[jk] | back: [A] | quit: [q] ";
+
+        let list = List::new(items).block(
+            Block::default()
+                .title_top(agenda_title)
+                .title_bottom(help_text)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+
+        let mut state = ListState::default();
+        state.select(selected_row);
+        f.render_stateful_widget(list, main_chunk, &mut state);
+
+        if let Some(top_chunk) = top_chunk_opt {
+            render_input_prompt(f, app, top_chunk, default_style, input_border_style, page_active_style);
+        }
+        render_confirm_popup(f, app, default_style, input_border_style);
+        return;
+    }
+
+    if app.view == View::Filter {
+        let today = Local::now().date_naive();
+        let entries = app.filter_entries();
+        let selected = app.selected_filter_index.min(entries.len().saturating_sub(1));
+        let header_style = Style::default().fg(neon_orange).add_modifier(Modifier::BOLD);
+
+        let mut items: Vec<ListItem> = vec![];
+        let mut selected_row = None;
+        let mut previous_page = None;
+        for (i, &(page_index, todo_index)) in entries.iter().enumerate() {
+            if previous_page != Some(page_index) {
+                if previous_page.is_some() {
+                    items.push(ListItem::new(""));
+                }
+                items.push(ListItem::new(format!(" {}", app.pages[page_index].name)).style(header_style));
+                previous_page = Some(page_index);
+            }
+
+            let todo = &app.pages[page_index].todos[todo_index];
+            let checkbox = if todo.completed { "[X] " } else { "[ ] " };
+            let selector = if i == selected { ">> " } else { "   " };
+            let style = if i == selected {
+                selected_style
+            } else if todo.completed {
+                done_style
+            } else {
+                default_style
+            };
+
+            let mut label = match todo.priority {
+                Some(priority) => format!("{} {}", priority.marker(), todo.name),
+                None => todo.name.clone(),
+            };
+            if let Some(due) = todo.due.filter(|_| !todo.completed) {
+                label.push_str(&format!("  [{}]", dates::format_due(due, today)));
+            }
+            if let Some(estimate) = todo.estimate.filter(|_| !todo.completed) {
+                label.push_str(&format!("  ~{}", format_estimate(estimate)));
+            }
+
+            if i == selected {
+                selected_row = Some(items.len());
+            }
+            items.push(ListItem::new(wrapped_item(&format!("{}{}", selector, checkbox), &label, (main_chunk.width as usize).saturating_sub(2))).style(style));
+        }
+        if entries.is_empty() && app.filter.is_some() {
+            items.push(ListItem::new("   no matching todos").style(done_style));
+        }
+
+        let prefix = format!(" {} ", app.context_prefix);
+        let mut matches = format!(" {} matches ", entries.len());
+        let mut title_spans = vec![Span::styled(prefix.clone(), Style::default().fg(neon_orange))];
+        match app.current_view {
+            Some(index) => {
+                // a saved view whose filter was edited but not saved again
+                if app.views[index].filter != app.filter_query {
+                    matches = format!(" {} (unsaved){}", app.filter_query, matches);
+                }
+                let available = (main_chunk.width as usize).saturating_sub(prefix.chars().count() + matches.chars().count() + 2);
+                title_spans.extend(tab_spans(app, available, page_active_style, page_inactive_style));
+            }
+            None => title_spans.push(Span::styled(format!(" {} ", app.filter_query), page_active_style)),
+        }
+        if app.filter.is_none() {
+            items.push(ListItem::new("   invalid filter - edit it with [/]").style(done_style));
+        }
+        let title = Line::from(title_spans);
+        let help_text = if app.current_view.is_some() {
+            " complete: [↵] | rename: [r] | due: [D] | repeat: [R] | estimate: [~] | notes: [e] | open page: [o] | edit filter: [/] | save view: [s] | delete view: [x] | nav: [↑↓→←],[hjkl] | back: [ESC] | quit: [q] "
+        } else {
+            " complete: [↵] | rename: [r] | due: [D] | repeat: [R] | estimate: [~] | notes: [e] | open page: [o] | edit filter: [/] | save view: [s] | nav: [↑↓→←],[hjkl] | back: [ESC] | quit: [q] "
+        };
+
+        let list = List::new(items).block(
+            Block::default()
+                .title_top(title)
+                .title_top(Line::styled(matches, page_inactive_style).right_aligned())
+                .title_bottom(help_text)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+
+        let mut state = ListState::default();
+        state.select(selected_row);
+        f.render_stateful_widget(list, main_chunk, &mut state);
+        if let Some(top_chunk) = top_chunk_opt {
+            render_input_prompt(f, app, top_chunk, default_style, input_border_style, page_active_style);
+        }
+        render_confirm_popup(f, app, default_style, input_border_style);
+        return;
+    }
+
+    if app.view == View::Board {
+        let columns = app.board_columns();
+        let cards = app.board_cards();
+        let outer = Block::default()
+            .title_top(Line::from(vec![
+                Span::styled(format!(" {} ", app.context_prefix), Style::default().fg(neon_orange)),
+                Span::styled(format!(" {} ", app.current_page().name), page_active_style),
+            ]))
+            .title_bottom(" move card: [HL] | nav: [hjkl] | complete: [↵] | rename: [r] | delete: [d] | notes: [e] | back: [b] | quit: [q] ")
+            .borders(Borders::ALL)
+            .border_type(ratatui::widgets::BorderType::Rounded)
+            .border_style(todo_border_style);
+        let inner = outer.inner(main_chunk);
+        f.render_widget(outer, main_chunk);
+
+        let column_areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, columns.len() as u32); columns.len()])
+            .split(inner);
+
+        for (column_index, (name, column_area)) in columns.iter().zip(column_areas.iter()).enumerate() {
+            let is_active_column = column_index == app.board_column_index;
+            let wrap_width = (column_area.width as usize).saturating_sub(2);
+            let items: Vec<ListItem> = cards[column_index]
+                .iter()
+                .enumerate()
+                .map(|(row, &index)| {
+                    let todo = &app.current_todos()[index];
+                    let is_selected = is_active_column && row == app.board_row_index;
+                    let style = if is_selected {
+                        selected_style
+                    } else if todo.completed {
+                        done_style
+                    } else {
+                        default_style
+                    };
+                    let selector = if is_selected { ">> " } else { "   " };
+                    ListItem::new(wrapped_item(selector, &todo.name, wrap_width)).style(style)
+                })
+                .collect();
+
+            let title_style = if is_active_column { page_active_style } else { page_inactive_style };
+            let list = List::new(items).block(
+                Block::default()
+                    .title_top(Span::styled(format!(" {} ({}) ", name, cards[column_index].len()), title_style))
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .border_style(if is_active_column { todo_border_style } else { input_border_style }),
+            );
+
+            let mut state = ListState::default();
+            if is_active_column && !cards[column_index].is_empty() {
+                state.select(Some(app.board_row_index));
+            }
+            f.render_stateful_widget(list, *column_area, &mut state);
+        }
+        if let Some(top_chunk) = top_chunk_opt {
+            render_input_prompt(f, app, top_chunk, default_style, input_border_style, page_active_style);
+        }
+        render_confirm_popup(f, app, default_style, input_border_style);
+        return;
+    }
+
+    if app.view == View::Stats {
+        let outer = Block::default()
+            .title_top(Line::from(vec![
+                Span::styled(format!(" {} ", app.context_prefix), Style::default().fg(neon_orange)),
+                Span::styled(" statistics ", page_active_style),
+            ]))
+            .title_bottom(" back: [S] | quit: [q] ")
+            .borders(Borders::ALL)
+            .border_type(ratatui::widgets::BorderType::Rounded)
+            .border_style(todo_border_style);
+        let inner = outer.inner(main_chunk);
+        f.render_widget(outer, main_chunk);
+
+        let table_height = (app.pages.len() as u16 + 4).min(inner.height / 2);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(table_height), Constraint::Min(8)])
+            .split(inner);
+
+        let all_todos: Vec<&Todo> = app.pages.iter().flat_map(|page| &page.todos).collect();
+        let row = |name: String, todos: &[&Todo]| {
+            let done = todos.iter().filter(|todo| todo.completed).count();
+            let total = todos.len();
+            let rate = (done * 100).checked_div(total).unwrap_or(0);
+            let remaining = remaining_estimate(todos.iter().copied());
+            let tracked = todos.iter().fold(chrono::Duration::zero(), |sum, todo| sum + todo.tracked_time());
+            let or_dash = |show: bool, value: String| if show { value } else { "-".to_string() };
+            Row::new(vec![
+                name,
+                (total - done).to_string(),
+                done.to_string(),
+                total.to_string(),
+                format!("{} {:>3}%", progress_bar(done, total, 10), rate),
+                or_dash(remaining > 0, format!("~{}", format_estimate(remaining))),
+                or_dash(tracked > chrono::Duration::zero(), format_duration(tracked)),
+            ])
+        };
+
+        let mut rows: Vec<Row> = app
+            .pages
+            .iter()
+            .map(|page| row(page.name.clone(), &page.todos.iter().collect::<Vec<_>>()).style(default_style))
+            .collect();
+        rows.push(row("all pages".to_string(), &all_todos).style(selected_style));
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(16),
+                Constraint::Length(10),
+                Constraint::Length(10),
+            ],
+        )
+        .header(Row::new(vec!["page", "open", "done", "total", "completion", "remaining", "tracked"]).style(done_style))
+        .block(
+            Block::default()
+                .title_top(" pages ")
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(input_border_style),
+        );
+        f.render_widget(table, chunks[0]);
+
+        let charts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+
+        let today = Local::now().date_naive();
+        let completed_on = |date: NaiveDate| {
+            all_todos
+                .iter()
+                .filter(|todo| todo.completed_at.is_some_and(|at| at.date_naive() == date))
+                .count() as u64
+        };
+
+        let day_bars: Vec<Bar> = (0..14)
+            .rev()
+            .map(|days_ago| {
+                let date = today - chrono::Duration::days(days_ago);
+                Bar::default()
+                    .value(completed_on(date))
+                    .label(Line::from(date.day().to_string()))
+                    .style(default_style)
+            })
+            .collect();
+        let daily = BarChart::default()
+            .data(BarGroup::default().bars(&day_bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .value_style(page_active_style)
+            .label_style(done_style)
+            .block(
+                Block::default()
+                    .title_top(" completed per day ")
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .border_style(input_border_style),
+            );
+        f.render_widget(daily, charts[0]);
+
+        // pairs of (added, completed) so the completion rate over time is visible
+        let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let week_groups: Vec<(String, Vec<Bar>)> = (0..8)
+            .rev()
+            .map(|weeks_ago| {
+                let start = week_start - chrono::Duration::weeks(weeks_ago);
+                let end = start + chrono::Duration::weeks(1);
+                let in_week = |at: Option<DateTime<Local>>| at.is_some_and(|at| at.date_naive() >= start && at.date_naive() < end);
+                let added = all_todos.iter().filter(|todo| in_week(todo.created_at)).count() as u64;
+                let completed = all_todos.iter().filter(|todo| in_week(todo.completed_at)).count() as u64;
+                (
+                    start.format("%m/%d").to_string(),
+                    vec![
+                        Bar::default().value(added).style(done_style),
+                        Bar::default().value(completed).style(default_style),
+                    ],
+                )
+            })
+            .collect();
+        let mut weekly = BarChart::default()
+            .bar_width(3)
+            .bar_gap(0)
+            .group_gap(1)
+            .value_style(page_active_style)
+            .label_style(done_style)
+            .block(
+                Block::default()
+                    .title_top(Line::from(vec![
+                        Span::raw(" "),
+                        Span::styled("added", done_style),
+                        Span::raw(" vs "),
+                        Span::styled("completed", default_style),
+                        Span::raw(" per week "),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .border_style(input_border_style),
+            );
+        for (label, bars) in &week_groups {
+            weekly = weekly.data(BarGroup::default().label(Line::from(label.as_str())).bars(bars));
+        }
+        f.render_widget(weekly, charts[1]);
+        return;
+    }
+
+    if app.view == View::Calendar {
+        let today = Local::now().date_naive();
+        let selected = app.calendar_date;
+        // a due date at the very end of chrono's range shows this month instead
+        let (grid_start, month_end) = calendar_grid(selected).or_else(|| calendar_grid(today)).unwrap();
+        let weeks = ((month_end - grid_start).num_days() / 7 + 1) as u16;
+
+        let mut open_counts: HashMap<NaiveDate, usize> = HashMap::new();
+        for todo in app.pages.iter().flat_map(|page| &page.todos) {
+            if let Some(due) = todo.due.filter(|_| !todo.completed) {
+                *open_counts.entry(due).or_default() += 1;
+            }
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(weeks * 2 + 3), Constraint::Min(3)])
+            .split(main_chunk);
+
+        let cell_width = (chunks[0].width.saturating_sub(2) / 7).max(4) as usize;
+        let mut lines = vec![Line::from(
+            ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+                .iter()
+                .map(|day| Span::styled(format!(" {:<width$}", day, width = cell_width - 1), done_style))
+                .collect::<Vec<_>>(),
+        )];
+
+        for week in 0..weeks {
+            let mut days = vec![];
+            let mut markers = vec![];
+            for weekday in 0..7 {
+                let date = grid_start + chrono::Duration::days((week * 7 + weekday) as i64);
+                let style = if date == selected {
+                    page_active_style
+                } else if date == today {
+                    selected_style
+                } else if date.month() != selected.month() {
+                    done_style
+                } else {
+                    default_style
+                };
+                let marker = match open_counts.get(&date) {
+                    Some(count) => format!("•{}", count),
+                    None => String::new(),
+                };
+                days.push(Span::styled(format!(" {:<width$}", date.day(), width = cell_width - 1), style));
+                markers.push(Span::styled(format!(" {:<width$}", marker, width = cell_width - 1), style));
+            }
+            lines.push(Line::from(days));
+            lines.push(Line::from(markers));
+        }
+
+        let calendar_title = Line::from(vec![
+            Span::styled(format!(" {} ", app.context_prefix), todo_border_style),
+            Span::styled(format!(" {} ", selected.format("%B %Y").to_string().to_lowercase()), page_active_style),
+        ]);
+        let grid = Paragraph::new(lines).block(
+            Block::default()
+                .title_top(calendar_title)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+        f.render_widget(grid, chunks[0]);
+
+        let items: Vec<ListItem> = app
+            .todos_due_on(selected)
+            .into_iter()
+            .map(|(page_index, todo_index)| {
+                let todo = &app.pages[page_index].todos[todo_index];
+                let checkbox = if todo.completed { "[X] " } else { "[ ] " };
+                let style = if todo.completed { done_style } else { default_style };
+                ListItem::new(format!("   {}{}  · {}", checkbox, todo.name, app.pages[page_index].name)).style(style)
+            })
+            .collect();
+
+        let help_text = " day: [hjkl] | month: [HL] | today: [.] | open: [↵] | back: [C] | quit: [q] ";
+        let list = List::new(items).block(
+            Block::default()
+                .title_top(format!(" due {} ", selected.format("%a %Y-%m-%d").to_string().to_lowercase()))
+                .title_bottom(help_text)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+        f.render_widget(list, chunks[1]);
+        render_confirm_popup(f, app, default_style, input_border_style);
+        return;
+    }
+
+    let (main_chunk, show_sidebar) = if app.settings.page_layout == PageLayout::Sidebar && main_chunk.width > 40 {
+        let longest_name = app.pages.iter().map(|page| page.name.chars().count()).max().unwrap_or(0);
+        let longest_name = u16::try_from(longest_name).unwrap_or(u16::MAX);
+        let sidebar_width = longest_name.saturating_add(14).clamp(20, (main_chunk.width / 3).max(20));
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(sidebar_width), Constraint::Min(20)])
+            .split(main_chunk);
+
+        let sidebar_items: Vec<ListItem> = app
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| {
+                let open = page.todos.iter().filter(|todo| !todo.completed).count();
+                let counts = format!("{}/{}", open, page.todos.len());
+                let name_width = (sidebar_width as usize).saturating_sub(counts.len() + 4);
+                let name: String = page.name.chars().take(name_width).collect();
+                let style = if i == app.current_page_index {
+                    page_active_style
+                } else {
+                    page_inactive_style
+                };
+                ListItem::new(format!(" {:<name_width$}{} ", name, counts)).style(style)
+            })
+            .collect();
+
+        let sidebar = List::new(sidebar_items).block(
+            Block::default()
+                .title_top(" pages ")
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        );
+        let mut sidebar_state = ListState::default();
+        sidebar_state.select(Some(app.current_page_index));
+        f.render_stateful_widget(sidebar, columns[0], &mut sidebar_state);
+
+        (columns[1], true)
+    } else {
+        (main_chunk, false)
+    };
+
+    let main_chunk = if app.settings.show_details && main_chunk.width > 60 {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(30), Constraint::Percentage(40)])
+            .split(main_chunk);
+
+        let details = Paragraph::new(detail_lines(app, default_style, preview_style))
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title_top(" details ")
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .border_style(todo_border_style),
+            );
+        f.render_widget(details, columns[1]);
+
+        columns[0]
+    } else {
+        main_chunk
+    };
+
+    let now = Local::now();
+    // border on both sides plus a column for the scrollbar
+    let wrap_width = (main_chunk.width as usize).saturating_sub(3);
+
+    let board_columns = app.board_columns();
+    let order = app.display_order();
+    let mut item_heights: Vec<usize> = Vec::with_capacity(order.len());
+    let mut items: Vec<ListItem> = order
+    .iter()
+    .map(|&i| (i, &app.current_todos()[i]))
+    .flat_map(|(i, todo)| {
+        let checkbox = if todo.completed {
+            "[X] "
+        } else if app.board_column_of(todo, &board_columns) > 0 {
+            "[~] "
+        } else {
+            "[ ] "
+        };
+        let blockers = if todo.completed { vec![] } else { app.blockers(todo) };
+        let style = if todo.completed {
+            done_style
+        } else if !blockers.is_empty() {
+            blocked_style
+        } else {
+            default_style
+        };
+        
+        let line_style = if i == app.selected_todo_index && !app.is_creating_todo {
+            selected_style
+        } else {
+            style
+        };
+
+        let selector = if i == app.selected_todo_index && !app.is_creating_todo { ">> " } else { "   " };
+        let mut name = match todo.priority {
+            Some(priority) => format!("{} {}", priority.marker(), todo.name),
+            None => todo.name.clone(),
+        };
+        if !todo.notes.is_empty() {
+            name.push_str(" ✎");
+        }
+        if let Some(due) = todo.due.filter(|_| !todo.completed) {
+            name.push_str(&format!("  [{}]", dates::format_due(due, now.date_naive())));
+        }
+        if let Some(estimate) = todo.estimate.filter(|_| !todo.completed) {
+            name.push_str(&format!("  ~{}", format_estimate(estimate)));
+        }
+        if todo.recurrence.is_some() {
+            name.push_str(" ↻");
+        }
+        if todo.is_timing() {
+            name.push_str(" ⏱");
+        }
+        if let Some(blocker) = blockers.first() {
+            name.push_str(&format!("  (blocked by '{}'", blocker.name));
+            if blockers.len() > 1 {
+                name.push_str(&format!(" +{}", blockers.len() - 1));
+            }
+            name.push(')');
+        }
+        if let Some(linking) = app.linking_todo.and_then(|id| app.todo_by_id(id)) {
+            if linking.id == todo.id {
+                name.push_str("  ◆");
+            } else if linking.depends_on.contains(&todo.id) {
+                name.push_str("  ⛓");
+            }
+        }
+        if app.settings.show_ages {
+            let age = if todo.completed { todo.completed_at } else { todo.created_at };
+            if let Some(at) = age {
+                name.push_str(&format!("  ({} ago)", format_age(now - at)));
+            }
+        }
+        let text = wrapped_item(&format!("{}{}", selector, checkbox), &name, wrap_width);
+        item_heights.push(text.height());
+        let mut result = vec![ListItem::new(text).style(line_style)];
+        
+        if app.is_creating_todo && i == app.selected_todo_index {
+            let preview_text = wrapped_item(">> [ ] ", &app.new_todo_input, wrap_width);
+            result.push(ListItem::new(preview_text).style(preview_style));
+        }
+        
+        result
+    })
+    .collect();
+
+    if order.is_empty() && app.is_creating_todo {
+        let preview_text = wrapped_item(">> [ ] ", &app.new_todo_input, wrap_width);
+        items.push(ListItem::new(preview_text).style(preview_style));
+    }
+
+    let hidden = app.current_todos().len() - order.len();
+    if hidden > 0 {
+        items.push(ListItem::new(format!("   ▸ {} completed - show: [c]", hidden)).style(done_style));
+        item_heights.push(1);
+    }
+
+    let done = app.current_todos().iter().filter(|todo| todo.completed).count();
+    let progress = format!(" {} {}/{} ", progress_bar(done, app.current_todos().len(), 10), done, app.current_todos().len());
+
+    let mut progress_spans = vec![];
+    if let Some((page_index, todo_index)) = app.active_timer() {
+        let todo = &app.pages[page_index].todos[todo_index];
+        let running = todo.time_log.last().map(|entry| Local::now() - entry.start).unwrap_or_default();
+        let name: String = todo.name.chars().take(20).collect();
+        progress_spans.push(Span::styled(format!(" ⏱ {} {} ", name, format_clock(running)), page_active_style));
+    }
+    let remaining = remaining_estimate(app.current_todos());
+    if remaining > 0 {
+        progress_spans.push(Span::styled(format!(" ~{} left ", format_estimate(remaining)), page_inactive_style));
+    }
+    let sort = app.current_page().sort;
+    if !sort.is_manual() {
+        progress_spans.push(Span::styled(format!(" ⇅ {} ", sort.label()), page_inactive_style));
+    }
+    progress_spans.push(Span::styled(progress, page_inactive_style));
+    let progress_width: usize = progress_spans.iter().map(|span| span.content.chars().count()).sum();
+
+    let prefix = format!(" {} ", app.context_prefix);
+    let mut title_spans = vec![
+        Span::styled(prefix.clone(), Style::default().fg(neon_orange))
+    ];
+    if show_sidebar {
+        title_spans.push(Span::styled(tab_labels(app).swap_remove(app.current_page_index), page_active_style));
+    } else {
+        // leave room for the borders and the progress gauge
+        let available = (main_chunk.width as usize).saturating_sub(prefix.chars().count() + progress_width + 2);
+        title_spans.extend(tab_spans(app, available, page_active_style, page_inactive_style));
+    }
+    let page_title = Line::from(title_spans);
+    
+    let todo_help = " new: [n] | rename: [r] | complete: [↵] | delete: [d] | trash: [t] | nav: [↑↓→←],[hjkl] | pages: [p],[1-9] | new page: [N] | due: [D] | repeat: [R] | estimate: [~] | priority: [!] | sort: [o] | blocked by: [B] | notes: [e] | edit page: [E] | agenda: [A] | calendar: [C] | board: [b] | stats: [S] | filter: [/] | timer: [T] | pomodoro: [P] | sidebar: [s] | details: [i] | ages: [a] | hide done: [c] | quit: [q] ";
+    let help_text = match app.linking_todo.and_then(|id| app.todo_by_id(id)) {
+        Some(linking) => {
+            let error = if app.input_error { " can't depend on itself or on something waiting for it |" } else { "" };
+            format!(
+                "{} pick what '{}' is blocked by - link/unlink: [↵] | nav: [↑↓→←],[hjkl] | pages: [p],[1-9] | cancel: [ESC] ",
+                error, linking.name
+            )
+        }
+        None => todo_help.to_string(),
+    };
+    
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title_top(page_title)
+                .title_top(Line::from(progress_spans).right_aligned())
+                .title_bottom(help_text)
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(todo_border_style),
+        )
+        .highlight_style(selected_style);
+
+    let selected_row = order.iter().position(|&i| i == app.selected_todo_index).unwrap_or(0);
+    let mut state = ListState::default();
+    if !order.is_empty() {
+        state.select(Some(selected_row));
+    }
+
+    f.render_stateful_widget(list, main_chunk, &mut state);
+
+    app.update_scrollbar(list_height, &item_heights, selected_row);
+
+    let content_height: usize = item_heights.iter().sum();
+    if !app.current_todos().is_empty() && list_height < content_height {
+        let scrollbar_area = Rect::new(
+            main_chunk.x + main_chunk.width - 1,
+            main_chunk.y + 1,
+            1,
+            main_chunk.height.saturating_sub(2),
+        );
+
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(None)
+            .thumb_symbol("▐")
+            .thumb_style(neon_orange);
+
+        f.render_stateful_widget(scrollbar, scrollbar_area, &mut app.scrollbar_state);
     }
 
-    fn edit_buffer(buf: &mut String, cursor_pos: &mut usize, key: KeyEvent) -> EditResult {
-        match key.code {
-            KeyCode::Enter => EditResult::Enter,
-            KeyCode::Esc => EditResult::Esc,
-            KeyCode::Char(c) => {
-                buf.insert(*cursor_pos, c);
-                *cursor_pos += 1;
-                EditResult::None
-            }
-            KeyCode::Backspace => {
-                if *cursor_pos > 0 {
-                    *cursor_pos -= 1;
-                    buf.remove(*cursor_pos);
-                }
-                EditResult::None
-            }
-            KeyCode::Left => {
-                if key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                    if *cursor_pos > 0 {
-                        let bytes = buf.as_bytes();
-                        let mut pos = *cursor_pos - 1;
-                        while pos > 0 && bytes[pos].is_ascii_whitespace() { pos -= 1; }
-                        while pos > 0 && !bytes[pos].is_ascii_whitespace() { pos -= 1; }
-                        if pos > 0 || bytes[0].is_ascii_whitespace() { pos += 1; }
-                        *cursor_pos = pos;
-                    }
-                } else if *cursor_pos > 0 {
-                    *cursor_pos -= 1;
-                }
-                EditResult::None
-            }
-            KeyCode::Right => {
-                if key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                    let len = buf.len();
-                    if *cursor_pos < len {
-                        let bytes = buf.as_bytes();
-                        let mut pos = *cursor_pos;
-                        while pos < len && !bytes[pos].is_ascii_whitespace() { pos += 1; }
-                        while pos < len && bytes[pos].is_ascii_whitespace() { pos += 1; }
-                        *cursor_pos = pos;
-                    }
-                } else if *cursor_pos < buf.len() {
-                    *cursor_pos += 1;
-                }
-                EditResult::None
-            }
-            _ => EditResult::None,
-        }
+    if let Some(top_chunk) = top_chunk_opt {
+        render_input_prompt(f, app, top_chunk, default_style, input_border_style, page_active_style);
     }
+
+    render_page_picker(f, app, default_style, selected_style, preview_style, input_border_style);
+    render_confirm_popup(f, app, default_style, input_border_style);
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum EditResult {
-    Enter,
-    Esc,
-    None,
+fn render_input_prompt(
+    f: &mut Frame,
+    app: &App,
+    top_chunk: Rect,
+    default_style: Style,
+    input_border_style: Style,
+    error_style: Style,
+) {
+    let prefix_len: u16 = 2;
+
+    let (input_title, input_text) = if app.is_creating_page {
+        (" new page - [↵]: save | [ESC]: cancel ", &app.new_page_name_input)
+    } else if app.is_renaming_page {
+        (" rename page - [↵]: save | {EMPTY}: delete page | [ESC]: cancel ", &app.rename_page_input)
+    } else if app.is_renaming_todo {
+        (" rename todo - [↵]: save | {EMPTY}: delete todo | [ESC]: cancel ", &app.rename_todo_input)
+    } else if app.is_setting_due {
+        (" due date (2026-03-14, today, tomorrow, fri, +3d, +2w) - [↵]: save | {EMPTY}: clear | [ESC]: cancel ", &app.due_input)
+    } else if app.is_setting_recurrence {
+        (" repeat (daily, weekdays, weekly mon,thu, monthly 1, every 3d) - [↵]: save | {EMPTY}: clear | [ESC]: cancel ", &app.recurrence_input)
+    } else if app.is_naming_view {
+        (" save view as - [↵]: save | [ESC]: cancel ", &app.view_name_input)
+    } else if app.is_filtering {
+        (" filter (tag:work, not done, due<7d, page:inbox, priority>=high, or, (...)) - [↵]: apply | [ESC]: cancel ", &app.filter_input)
+    } else if app.is_setting_estimate {
+        (" estimate (30m, 2h, 1h30m, 1.5h) - [↵]: save | {EMPTY}: clear | [ESC]: cancel ", &app.estimate_input)
+    } else {
+        (" new todo (end with ~2h to estimate) - [↵]: save | [ESC]: cancel ", &app.new_todo_input)
+    };
+
+    let input_title = if app.input_error {
+        Line::from(vec![
+            Span::raw(input_title),
+            Span::styled(" invalid ", error_style),
+        ])
+    } else {
+        Line::from(input_title)
+    };
+
+    let display_text = format!("* {}", input_text);
+    let input = Paragraph::new(display_text.as_str())
+        .style(default_style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .title(input_title)
+                .border_style(input_border_style),
+        );
+    f.render_widget(input, top_chunk);
+    f.set_cursor_position(
+        ratatui::layout::Position::new(
+            top_chunk.x + 1 + prefix_len + app.cursor_position as u16,
+            top_chunk.y + 1,
+        ),
+    );
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+fn progress_bar(done: usize, total: usize, width: usize) -> String {
+    let filled = (done * width).checked_div(total).unwrap_or(0);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
 
-    let mut app = App::new();
-    let res = run_app(&mut terminal, &mut app);
+// the pages followed by the saved views
+fn tab_labels(app: &App) -> Vec<String> {
+    let pages = app.pages.iter().enumerate().map(|(i, page)| format!(" {}: {} ", i + 1, page.name));
+    let views = app.views.iter().map(|view| format!(" ◇ {} ", view.name));
+    pages.chain(views).collect()
+}
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+// the tabs around the current one that fit in `available` columns, with markers for the rest
+fn tab_spans(app: &App, available: usize, page_active_style: Style, page_inactive_style: Style) -> Vec<Span<'static>> {
+    let labels = tab_labels(app);
+    let current = app.current_tab();
+    // room for the overflow markers
+    let (start, end) = visible_page_range(&labels, current, available.saturating_sub(4));
 
-    if let Err(err) = res {
-        println!("{:?}", err)
+    let mut spans = vec![];
+    if start > 0 {
+        spans.push(Span::styled("‹ ", page_inactive_style));
     }
-
-    Ok(())
+    let count = labels.len();
+    for (i, label) in labels.into_iter().enumerate().take(end).skip(start) {
+        let style = if i == current {
+            page_active_style
+        } else {
+            page_inactive_style
+        };
+        spans.push(Span::styled(label, style));
+    }
+    if end < count {
+        spans.push(Span::styled(" ›", page_inactive_style));
+    }
+    spans
 }
 
-fn run_app<B: ratatui::backend::Backend>(
-    terminal: &mut Terminal<B>,
-    app: &mut App,
-) -> io::Result<()> {
-    let tick_rate = Duration::from_millis(TICK_RATE_MS);
-    let mut last_tick = Instant::now();
+// widest run of tabs around the current page that fits in the available width
+fn visible_page_range(labels: &[String], current: usize, available: usize) -> (usize, usize) {
+    if labels.is_empty() {
+        return (0, 0);
+    }
 
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+    let widths: Vec<usize> = labels.iter().map(|label| label.chars().count()).collect();
+    let mut start = current;
+    let mut end = current + 1;
+    let mut used = widths[current];
 
-        if app.should_quit {
-            return Ok(());
+    loop {
+        let mut grew = false;
+        if end < widths.len() && used + widths[end] <= available {
+            used += widths[end];
+            end += 1;
+            grew = true;
+        }
+        if start > 0 && used + widths[start - 1] <= available {
+            start -= 1;
+            used += widths[start];
+            grew = true;
         }
+        if !grew {
+            break;
+        }
+    }
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+    (start, end)
+}
 
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind != KeyEventKind::Press {
-                } else {
-                    if app.process_input_event(key) {
-                    } else {
-                        match key.code {
-                            KeyCode::Char('q') => {
-                                app.should_quit = true;
-                            }
-                            KeyCode::Char('n') => {
-                                app.is_creating_todo = true;
-                                app.cursor_position = app.new_todo_input.len();
-                            }
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
-                                    if !app.current_todos().is_empty() && app.current_todos().len() > 1 {
-                                        let current = app.selected_todo_index;
-                                        let next = (current + 1) % app.current_todos().len();
-                                        app.current_todos_mut().swap(current, next);
-                                        app.selected_todo_index = next;
-                                        app.save_app_data().unwrap();
-                                    }
-                                } else {
-                                    if !app.current_todos().is_empty() {
-                                        app.selected_todo_index = (app.selected_todo_index + 1) % app.current_todos().len();
-                                    }
-                                }
-                            }
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
-                                    if !app.current_todos().is_empty() && app.current_todos().len() > 1 {
-                                        let current = app.selected_todo_index;
-                                        let prev = (current + app.current_todos().len() - 1) % app.current_todos().len();
-                                        app.current_todos_mut().swap(current, prev);
-                                        app.selected_todo_index = prev;
-                                        app.save_app_data().unwrap();
-                                    }
-                                } else {
-                                    if !app.current_todos().is_empty() {
-                                        app.selected_todo_index = (app.selected_todo_index + app.current_todos().len() - 1) % app.current_todos().len();
-                                    }
-                                }
-                            }
-                            KeyCode::Enter => {
-                                if !app.current_todos().is_empty() {
-                                    let index = app.selected_todo_index;
-                                    let todo = &mut app.current_todos_mut()[index];
-                                    todo.completed = !todo.completed;
-                                    app.save_app_data().unwrap();
-                                }
-                            }
-                            KeyCode::Char('d') => {
-                                if !app.current_todos().is_empty() {
-                                    let index = app.selected_todo_index;
-                                    app.current_todos_mut().remove(index);
-                                    if app.current_todos().is_empty() {
-                                        app.selected_todo_index = 0;
-                                    } else if app.selected_todo_index >= app.current_todos().len() {
-                                        app.selected_todo_index = app.current_todos().len() - 1;
-                                    }
-                                    app.save_app_data().unwrap();
-                                }
-                            }
-                            KeyCode::Char('r') => {
-                                if !app.current_todos().is_empty() {
-                                    let index = app.selected_todo_index;
-                                    app.rename_todo_input = app.current_todos()[index].name.clone();
-                                    app.cursor_position = app.rename_todo_input.len();
-                                    app.is_renaming_todo = true;
-                                }
-                            }
-                            KeyCode::Right | KeyCode::Char('l') => {
-                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
-                                    if app.pages.len() > 1 {
-                                        let current = app.current_page_index;
-                                        let next = (current + 1) % app.pages.len();
-                                        app.pages.swap(current, next);
-                                        app.current_page_index = next;
-                                        app.save_app_data().unwrap();
-                                    }
-                                } else {
-                                    if !app.pages.is_empty() {
-                                        app.current_page_index = (app.current_page_index + 1) % app.pages.len();
-                                        app.selected_todo_index = 0;
-                                    }
-                                }
-                            }
-                            KeyCode::Left | KeyCode::Char('h') => {
-                                if key.modifiers.contains(event::KeyModifiers::SHIFT) {
-                                    if app.pages.len() > 1 {
-                                        let current = app.current_page_index;
-                                        let prev = (current + app.pages.len() - 1) % app.pages.len();
-                                        app.pages.swap(current, prev);
-                                        app.current_page_index = prev;
-                                        app.save_app_data().unwrap();
-                                    }
-                                } else {
-                                    if !app.pages.is_empty() {
-                                        app.current_page_index = (app.current_page_index + app.pages.len() - 1) % app.pages.len();
-                                        app.selected_todo_index = 0;
-                                    }
-                                }
-                            }
-                            KeyCode::Char(c @ '1'..='9') => {
-                                if let Some(digit) = c.to_digit(10) {
-                                    let page_index = (digit - 1) as usize;
-                                    if page_index < app.pages.len() {
-                                        if page_index == app.current_page_index {
-                                            app.rename_page_input = app.current_page().name.clone();
-                                            app.cursor_position = app.rename_page_input.len();
-                                            app.is_renaming_page = true;
-                                        } else {
-                                            app.current_page_index = page_index;
-                                            app.selected_todo_index = 0;
-                                        }
-                                    } else {
-                                        app.is_creating_page = true;
-                                        app.new_page_name_input.clear();
-                                        app.cursor_position = 0;
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        }
+// case-insensitive subsequence match, rewarding consecutive and early matches
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
 
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
+    for q in query.to_lowercase().chars() {
+        let found = candidate[position..].iter().position(|&c| c == q)? + position;
+        score += match previous {
+            Some(p) if p + 1 == found => 5,
+            _ => 1,
+        };
+        if found == 0 {
+            score += 3;
         }
+        previous = Some(found);
+        position = found + 1;
     }
+
+    Some(score * 100 - candidate.len() as i32)
 }
 
-fn ui(f: &mut Frame, app: &mut App) {
-    let is_in_input_mode = app.is_creating_todo || app.is_creating_page || app.is_renaming_page || app.is_renaming_todo;
-    
-    let top_needed: u16 = if is_in_input_mode { 3 } else { 0 };
+fn render_page_picker(
+    f: &mut Frame,
+    app: &App,
+    text_style: Style,
+    selected_style: Style,
+    create_style: Style,
+    border_style: Style,
+) {
+    if !app.is_picking_page {
+        return;
+    }
 
-    let list_min_height: u16 = 5;
+    let mut items: Vec<ListItem> = app
+        .picker_matches()
+        .into_iter()
+        .map(|i| {
+            let page = &app.pages[i];
+            ListItem::new(format!("{}: {} ({})", i + 1, page.name, page.todos.len())).style(text_style)
+        })
+        .collect();
+    if app.picker_can_create() {
+        items.push(ListItem::new(format!("+ new page '{}'", app.page_picker_input)).style(create_style));
+    }
+    let item_count = items.len();
 
-    let constraints = if top_needed > 0 {
-        vec![
-            Constraint::Length(top_needed),
-            Constraint::Min(list_min_height),
-        ]   
-    } else {
-        vec![Constraint::Min(list_min_height)]
-    };
+    let height = (item_count as u16 + 5).clamp(6, 20);
+    let area = centered_rect(60, height, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(ratatui::widgets::BorderType::Rounded)
+        .title(" pages - [↵]: go / create | [ESC]: cancel ")
+        .border_style(border_style);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(&constraints)
-        .split(f.area());
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
 
-    let (top_chunk_opt, main_chunk) = if top_needed > 0 {
-        (Some(chunks[0]), chunks[1])
-    } else {
-        (None, chunks[0])
-    };
+    let input = Paragraph::new(format!("* {}", app.page_picker_input)).style(text_style);
+    f.render_widget(input, chunks[0]);
 
-    let neon_orange = Color::Rgb(255, 140, 0);
-    let bright_orange = Color::Rgb(255, 165, 0);
-    let dark_orange = Color::Rgb(180, 82, 0);
-    let selected_style = Style::default().fg(Color::White);
-    let done_style = Style::default().fg(dark_orange);
-    let default_style = Style::default().fg(bright_orange);
-    let preview_style = Style::default().fg(Color::Rgb(100, 100, 100));
-    let todo_border_style = Style::default().fg(neon_orange);
-    let input_border_style = Style::default().fg(bright_orange);
-    let page_active_style = Style::default().fg(Color::Black).bg(neon_orange);
-    let page_inactive_style = Style::default().fg(bright_orange);
+    let list = List::new(items)
+        .highlight_style(selected_style)
+        .highlight_symbol(">> ");
+    let mut state = ListState::default();
+    if item_count > 0 {
+        state.select(Some(app.selected_picker_index.min(item_count - 1)));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    f.set_cursor_position(
+        ratatui::layout::Position::new(
+            chunks[0].x + 2 + app.cursor_position as u16,
+            chunks[0].y,
+        ),
+    );
+}
+
+// wraps text at word boundaries, indenting continuation lines under the first
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for word in text.split(' ') {
+        let word_len = word.chars().count();
+        if line_len > 0 && line_len + 1 + word_len > width {
+            lines.push(std::mem::take(&mut line));
+            line_len = 0;
+        }
+        if line_len > 0 {
+            line.push(' ');
+            line_len += 1;
+        }
+
+        let mut chars = word.chars().peekable();
+        while chars.peek().is_some() {
+            if line_len == width {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            let take = width - line_len;
+            let chunk: String = chars.by_ref().take(take).collect();
+            line_len += chunk.chars().count();
+            line.push_str(&chunk);
+        }
+    }
+    lines.push(line);
+    lines
+}
 
-    let list_height = (main_chunk.height.saturating_sub(2)) as usize;
+fn wrapped_item(prefix: &str, text: &str, width: usize) -> Text<'static> {
+    let indent = prefix.chars().count();
+    let lines: Vec<Line> = wrap_text(text, width.saturating_sub(indent))
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                Line::from(format!("{}{}", prefix, line))
+            } else {
+                Line::from(format!("{}{}", " ".repeat(indent), line))
+            }
+        })
+        .collect();
+    Text::from(lines)
+}
 
-    let mut items: Vec<ListItem> = app
-    .current_todos()
-    .iter()
-    .enumerate()
-    .flat_map(|(i, todo)| {
-        let checkbox = if todo.completed { "[X] " } else { "[ ] " };
-        let style = if todo.completed { done_style } else { default_style };
-        
-        let line_style = if i == app.selected_todo_index && !app.is_creating_todo {
-            selected_style
-        } else {
-            style
-        };
+fn detail_lines(app: &App, text_style: Style, label_style: Style) -> Vec<Line<'static>> {
+    let Some(todo) = app.current_todos().get(app.selected_todo_index) else {
+        return vec![Line::styled("no todo selected", label_style)];
+    };
 
-        let selector = if i == app.selected_todo_index && !app.is_creating_todo { ">> " } else { "   " };
-        let mut result = vec![ListItem::new(format!("{}{}{}", selector, checkbox, todo.name)).style(line_style)];
-        
-        if app.is_creating_todo && i == app.selected_todo_index {
-            let preview_text = format!(">> [ ] {}", app.new_todo_input);
-            result.push(ListItem::new(preview_text).style(preview_style));
+    let columns = app.board_columns();
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), label_style),
+            Span::styled(value, text_style),
+        ])
+    };
+
+    let mut lines = vec![
+        Line::styled(todo.name.clone(), text_style),
+        Line::default(),
+        field("status", columns[app.board_column_of(todo, &columns)].clone()),
+        field("page", format!("{} (id {})", app.current_page().name, app.current_page().id)),
+        field("position", format!("{} of {}", app.selected_todo_index + 1, app.current_todos().len())),
+        field("id", todo.id.to_string()),
+    ];
+
+    let today = Local::now().date_naive();
+    if let Some(due) = todo.due {
+        lines.push(field("due", format!("{} ({})", due.format("%Y-%m-%d"), dates::format_due(due, today))));
+    }
+    if let Some(priority) = todo.priority {
+        lines.push(field("priority", priority.label().to_string()));
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        lines.push(field("repeats", recurrence.to_string()));
+    }
+    if !todo.depends_on.is_empty() {
+        let dependencies: Vec<String> = todo
+            .depends_on
+            .iter()
+            .filter_map(|id| app.todo_by_id(*id))
+            .map(|dependency| format!("{} {}", if dependency.completed { "[X]" } else { "[ ]" }, dependency.name))
+            .collect();
+        lines.push(field("depends on", dependencies.join(", ")));
+    }
+    let blocks: Vec<&str> = app
+        .pages
+        .iter()
+        .flat_map(|page| &page.todos)
+        .filter(|other| !other.completed && other.depends_on.contains(&todo.id))
+        .map(|other| other.name.as_str())
+        .collect();
+    if !blocks.is_empty() {
+        lines.push(field("blocks", blocks.join(", ")));
+    }
+    if let Some(estimate) = todo.estimate {
+        let mut value = format_estimate(estimate);
+        if !todo.time_log.is_empty() {
+            let tracked = todo.tracked_time().num_minutes();
+            value.push_str(&format!(" ({}% tracked", tracked * 100 / estimate.max(1)));
+            if tracked > estimate {
+                value.push_str(&format!(", {} over", format_duration(chrono::Duration::minutes(tracked - estimate))));
+            }
+            value.push(')');
         }
-        
-        result
-    })
-    .collect();
+        lines.push(field("estimate", value));
+    }
+    if todo.pomodoros > 0 {
+        lines.push(field("pomodoros", todo.pomodoros.to_string()));
+    }
+    if !todo.time_log.is_empty() {
+        let running = if todo.is_timing() { " (running)" } else { "" };
+        lines.push(field("tracked", format!("{}{}", format_duration(todo.tracked_time()), running)));
+    }
 
-    if app.current_todos().is_empty() && app.is_creating_todo {
-        let preview_text = format!(">> [ ] {}", app.new_todo_input);
-        items.push(ListItem::new(preview_text).style(preview_style));
+    let timestamps = [
+        ("created", todo.created_at),
+        ("completed", todo.completed_at),
+        ("modified", todo.modified_at),
+    ];
+    for (label, at) in timestamps {
+        if let Some(at) = at {
+            lines.push(field(label, format!("{} ({} ago)", at.format("%Y-%m-%d %H:%M"), format_age(Local::now() - at))));
+        }
     }
 
-    let page_spans: Vec<Span> = app.pages.iter().enumerate().map(|(i, page)| {
-        let style = if i == app.current_page_index {
-            page_active_style
-        } else {
-            page_inactive_style
-        };
-        Span::styled(format!(" {}: {} ", i + 1, page.name), style)
-    }).collect();
+    if !todo.notes.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::styled("notes:", label_style));
+        lines.extend(todo.notes.lines().map(|line| Line::styled(line.to_string(), text_style)));
+    }
 
-    let mut title_spans = vec![
-        Span::styled(format!(" {} ", app.context_prefix), Style::default().fg(neon_orange))
-    ];
-    title_spans.extend(page_spans);
-    let page_title = Line::from(title_spans);
-    
-    let help_text = " new: [n] | rename: [r] | complete: [↵] | delete: [d] | nav: [↑↓→←],[hjkl] | new/rename page: [1-9] | quit: [q] ";
-    
-    let list = List::new(items)
+    lines
+}
+
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn render_confirm_popup(f: &mut Frame, app: &App, text_style: Style, border_style: Style) {
+    let Some(action) = app.pending_action else {
+        return;
+    };
+
+    let message = app.describe_action(action);
+    let width = (message.chars().count() as u16 + 6).max(30);
+    let area = centered_rect(width, 5, f.area());
+
+    let popup = Paragraph::new(message)
+        .style(text_style)
+        .alignment(ratatui::layout::Alignment::Center)
         .block(
             Block::default()
-                .title_top(page_title)
-                .title_bottom(help_text)
                 .borders(Borders::ALL)
                 .border_type(ratatui::widgets::BorderType::Rounded)
-                .border_style(todo_border_style),
-        )
-        .highlight_style(selected_style);
+                .title(" confirm ")
+                .title_bottom(" yes: [y/↵] | no: [n/ESC] ")
+                .border_style(border_style)
+                .padding(ratatui::widgets::Padding::vertical(1)),
+        );
 
-    let mut state = ListState::default();
-    if !(app.is_creating_todo && app.current_todos().is_empty()) {
-        state.select(Some(app.selected_todo_index));
-    }
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
 
-    f.render_stateful_widget(list, main_chunk, &mut state);
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
 
-    app.update_scrollbar(list_height);
+// `30m`, `2h`, `1h30m`, `1.5h` or a bare number of minutes, with an optional leading `~`
+fn parse_estimate(input: &str) -> Option<i64> {
+    let input = input.trim().trim_start_matches('~').to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = input.parse::<i64>() {
+        return (minutes > 0).then_some(minutes);
+    }
 
-    let current_todos_len = app.current_todos().len();
-    if !app.current_todos().is_empty() && list_height < current_todos_len {
-        let scrollbar_area = Rect::new(
-            main_chunk.x + main_chunk.width - 1,
-            main_chunk.y + 1,
-            1,
-            main_chunk.height.saturating_sub(2),
-        );
+    let mut minutes = 0.0;
+    let mut number = String::new();
+    for c in input.chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'h' | 'm' | 'd' if !number.is_empty() => {
+                let value: f64 = number.parse().ok()?;
+                minutes += value * match c {
+                    'd' => 8.0 * 60.0,
+                    'h' => 60.0,
+                    _ => 1.0,
+                };
+                number.clear();
+            }
+            ' ' => {}
+            _ => return None,
+        }
+    }
+    // `as` would quietly saturate anything too big for an i64
+    let minutes = minutes.round();
+    if !number.is_empty() || !(1.0..i64::MAX as f64).contains(&minutes) {
+        return None;
+    }
+    Some(minutes as i64)
+}
 
-        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(None)
-            .end_symbol(None)
-            .track_symbol(None)
-            .thumb_symbol("▐")
-            .thumb_style(neon_orange);
+fn format_estimate(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{:02}m", h, m),
+    }
+}
 
-        f.render_stateful_widget(scrollbar, scrollbar_area, &mut app.scrollbar_state);
+// splits a trailing `~2h` off a new todo's name. the unit is required here so a name that
+// just ends in a number like `call back ~10` is left alone
+fn split_estimate(input: &str) -> (String, Option<i64>) {
+    let input = input.trim();
+    if let Some((name, estimate)) = input.rsplit_once(" ~") {
+        let has_unit = estimate.ends_with(['m', 'h', 'd', 'M', 'H', 'D']);
+        if let Some(minutes) = parse_estimate(estimate).filter(|_| has_unit) {
+            return (name.trim_end().to_string(), Some(minutes));
+        }
     }
+    (input.to_string(), None)
+}
 
-    if let Some(top_chunk) = top_chunk_opt {
-        let prefix_len: u16 = 2;
-        
-        if app.is_creating_page {
-            let input_title = " new page - [↵]: save | [ESC]: cancel ";
-            let display_text = format!("* {}", app.new_page_name_input);
-            let input = Paragraph::new(display_text.as_str())
-                .style(default_style)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(ratatui::widgets::BorderType::Rounded)
-                        .title(input_title)
-                        .border_style(input_border_style),
-                );
-            f.render_widget(input, top_chunk);
-            f.set_cursor_position(
-                ratatui::layout::Position::new(
-                    top_chunk.x + 1 + prefix_len + app.cursor_position as u16,
-                    top_chunk.y + 1,
-                ),
-            );
-        } else if app.is_renaming_page {
-            let input_title = " rename page - [↵]: save | {EMPTY}: delete page | [ESC]: cancel ";
-            let display_text = format!("* {}", app.rename_page_input);
-            let input = Paragraph::new(display_text.as_str())
-                .style(default_style)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(ratatui::widgets::BorderType::Rounded)
-                        .title(input_title)
-                        .border_style(input_border_style),
-                );
-            f.render_widget(input, top_chunk);
-            f.set_cursor_position(
-                ratatui::layout::Position::new(
-                    top_chunk.x + 1 + prefix_len + app.cursor_position as u16,
-                    top_chunk.y + 1,
-                ),
-            );
-        } else if app.is_renaming_todo {
-            let input_title = " rename todo - [↵]: save | {EMPTY}: delete todo | [ESC]: cancel ";
-            let display_text = format!("* {}", app.rename_todo_input);
-            let input = Paragraph::new(display_text.as_str())
-                .style(default_style)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(ratatui::widgets::BorderType::Rounded)
-                        .title(input_title)
-                        .border_style(input_border_style),
-                );
-            f.render_widget(input, top_chunk);
-            f.set_cursor_position(
-                ratatui::layout::Position::new(
-                    top_chunk.x + 1 + prefix_len + app.cursor_position as u16,
-                    top_chunk.y + 1,
-                ),
-            );
-        } else if app.is_creating_todo {
-            let input_title = " new todo - [↵]: save | [ESC]: cancel ";
-            let display_text = format!("* {}", app.new_todo_input);
-            let input = Paragraph::new(display_text.as_str())
-                .style(default_style)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(ratatui::widgets::BorderType::Rounded)
-                        .title(input_title)
-                        .border_style(input_border_style),
-                );
-            f.render_widget(input, top_chunk);
-            f.set_cursor_position(
-                ratatui::layout::Position::new(
-                    top_chunk.x + 1 + prefix_len + app.cursor_position as u16,
-                    top_chunk.y + 1,
-                ),
-            );
+// (page index, todo index) of the todo with the given id
+fn find_todo(pages: &[Page], id: u64) -> Option<(usize, usize)> {
+    pages.iter().enumerate().find_map(|(page_index, page)| {
+        page.todos
+            .iter()
+            .position(|todo| todo.id == id)
+            .map(|todo_index| (page_index, todo_index))
+    })
+}
+
+// whether `from` waits on `to`, directly or through other todos. a link from `to` to `from`
+// would then make a todo wait on itself
+fn depends_on_transitively(pages: &[Page], from: u64, to: u64) -> bool {
+    let mut stack = vec![from];
+    let mut seen = vec![];
+    while let Some(id) = stack.pop() {
+        if id == to {
+            return true;
+        }
+        if seen.contains(&id) {
+            continue;
         }
+        seen.push(id);
+        if let Some(todo) = pages.iter().flat_map(|page| &page.todos).find(|todo| todo.id == id) {
+            stack.extend(&todo.depends_on);
+        }
+    }
+    false
+}
+
+// estimated minutes left across the open todos
+fn remaining_estimate<'a>(todos: impl IntoIterator<Item = &'a Todo>) -> i64 {
+    todos
+        .into_iter()
+        .filter(|todo| !todo.completed)
+        .filter_map(|todo| todo.estimate)
+        .fold(0, i64::saturating_add)
+}
+
+fn format_clock(duration: chrono::Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+fn format_age(age: chrono::Duration) -> String {
+    if age.num_days() > 0 {
+        format!("{}d", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h", age.num_hours())
+    } else {
+        format!("{}m", age.num_minutes().max(0))
     }
 }
 
@@ -735,22 +3823,57 @@ fn get_data_path() -> Result<PathBuf, Box<dyn Error>> {
     Ok(home_path)
 }
 
-fn load_app_data() -> Result<Vec<Page>, Box<dyn Error>> {
+fn load_app_data() -> Result<AppData, Box<dyn Error>> {
     let path = get_data_path()?;
     if !path.exists() {
-        return Ok(vec![]);
+        return Ok(AppData::default());
     }
 
-    let file = File::open(path)?;
+    let file = File::open(&path)?;
     let mut reader = BufReader::new(file);
     let mut contents = String::new();
     reader.read_to_string(&mut contents)?;
 
-    let pages: Vec<Page> = serde_json::from_str(&contents)?;
-    Ok(pages)
+    // older data files are a bare list of pages. anything else that doesn't parse is an error
+    // rather than an empty list, which the next save would write over the file
+    let mut data = match serde_json::from_str::<AppData>(&contents) {
+        Ok(data) => data,
+        Err(err) => match serde_json::from_str::<Vec<Page>>(&contents) {
+            Ok(pages) => AppData { pages, ..AppData::default() },
+            Err(_) => return Err(format!("can't read {}: {}", path.display(), err).into()),
+        },
+    };
+    assign_ids(&mut data);
+    Ok(data)
+}
+
+// gives pages and todos from older files an id and makes sure new ids don't reuse any.
+// pages and todos share one sequence so an id is never ambiguous
+fn assign_ids(data: &mut AppData) {
+    let mut pages: Vec<&mut Page> = data.pages.iter_mut().collect();
+    let mut todos: Vec<&mut Todo> = vec![];
+    for entry in &mut data.trash {
+        match &mut entry.item {
+            TrashedItem::Todo { todo, .. } => todos.push(todo),
+            TrashedItem::Page { page, .. } => pages.push(page),
+        }
+    }
+    let mut ids: Vec<&mut u64> = vec![];
+    for page in pages {
+        ids.push(&mut page.id);
+        ids.extend(page.todos.iter_mut().map(|todo| &mut todo.id));
+    }
+    ids.extend(todos.into_iter().map(|todo| &mut todo.id));
+
+    let max_id = ids.iter().map(|id| **id).max().unwrap_or(0);
+    data.next_id = data.next_id.max(max_id + 1);
+    for id in ids.into_iter().filter(|id| **id == 0) {
+        *id = take_id(&mut data.next_id);
+    }
 }
 
-fn save_app_data(pages: &[Page]) -> Result<(), Box<dyn Error>> {
+// takes an `AppData` or, to save without cloning everything, an `AppDataRef`
+fn save_app_data(data: &impl Serialize) -> Result<(), Box<dyn Error>> {
     let path = get_data_path()?;
     let file = OpenOptions::new()
         .write(true)
@@ -759,8 +3882,593 @@ fn save_app_data(pages: &[Page]) -> Result<(), Box<dyn Error>> {
         .open(path)?;
     let mut writer = BufWriter::new(file);
 
-    let json = serde_json::to_string_pretty(pages)?;
+    let json = serde_json::to_string_pretty(data)?;
     writer.write_all(json.as_bytes())?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(pages: &[(&str, &[&str])]) -> App {
+        let mut data = AppData::default();
+        for (name, todos) in pages {
+            let mut page = Page::new(take_id(&mut data.next_id), name.to_string());
+            for name in *todos {
+                page.todos.push(Todo::new(take_id(&mut data.next_id), name.to_string()));
+            }
+            data.pages.push(page);
+        }
+        App::from_data(data)
+    }
+
+    fn names(todos: &[Todo]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.name.as_str()).collect()
+    }
+
+    #[test]
+    fn restores_a_todo_where_it_was() {
+        let mut app = app(&[("home", &["a", "b", "c"]), ("work", &[])]);
+        app.trash_todo(1);
+        assert_eq!(names(app.current_todos()), ["a", "c"]);
+
+        app.current_page_index = 1;
+        app.restore_trash_entry(0);
+        assert!(app.trash.is_empty());
+        assert_eq!((app.current_page_index, app.selected_todo_index), (0, 1));
+        assert_eq!(names(app.current_todos()), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn restoring_a_todo_brings_back_its_page() {
+        let mut app = app(&[("home", &["a"]), ("work", &[])]);
+        app.trash_todo(0);
+        app.trash_page(0);
+
+        app.restore_trash_entry(0);
+        assert_eq!(app.current_page().name, "home");
+        assert_eq!(names(app.current_todos()), ["a"]);
+    }
+
+    #[test]
+    fn restores_a_todo_to_its_page_after_a_rename() {
+        let mut app = app(&[("home", &["a"]), ("work", &[])]);
+        app.trash_todo(0);
+        app.pages[0].name = "work".to_string();
+        app.pages[1].name = "home".to_string();
+
+        app.restore_trash_entry(0);
+        assert_eq!(app.current_page_index, 0);
+        assert_eq!(names(app.current_todos()), ["a"]);
+    }
+
+    #[test]
+    fn older_trash_entries_are_restored_by_page_name() {
+        let mut app = app(&[("home", &[]), ("work", &[])]);
+        app.trash.push(TrashEntry {
+            item: serde_json::from_str(r#"{"Todo": {"page": "work", "position": 0, "todo": {"name": "a", "completed": false}}}"#)
+                .unwrap(),
+            deleted_at: Local::now(),
+        });
+
+        app.restore_trash_entry(0);
+        assert_eq!(app.current_page().name, "work");
+        assert_eq!(names(app.current_todos()), ["a"]);
+    }
+
+    #[test]
+    fn restores_a_page_at_its_position() {
+        let mut app = app(&[("a", &[]), ("b", &["x"]), ("c", &[])]);
+        app.current_page_index = 1;
+        app.trash_page(1);
+        assert_eq!(app.pages.len(), 2);
+
+        app.restore_trash_entry(0);
+        let pages: Vec<&str> = app.pages.iter().map(|page| page.name.as_str()).collect();
+        assert_eq!(pages, ["a", "b", "c"]);
+        assert_eq!(app.current_page_index, 1);
+        assert_eq!(names(app.current_todos()), ["x"]);
+    }
+
+    #[test]
+    fn purges_entries_and_expired_trash() {
+        let mut app = app(&[("home", &["a", "b", "c"])]);
+        for _ in 0..3 {
+            app.trash_todo(0);
+        }
+        app.selected_trash_index = 2;
+        app.purge_trash_entry(2);
+        assert_eq!(app.trash.len(), 2);
+        assert_eq!(app.selected_trash_index, 1);
+
+        app.trash[0].deleted_at = Local::now() - chrono::Duration::days(31);
+        assert!(app.purge_expired_trash());
+        assert_eq!(app.trash.len(), 1);
+        assert!(!app.purge_expired_trash());
+
+        // 0 keeps everything
+        app.settings.trash_retention_days = 0;
+        app.trash[0].deleted_at = Local::now() - chrono::Duration::days(365);
+        assert!(!app.purge_expired_trash());
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.process_input_event(KeyEvent::from(code));
+    }
+
+    #[test]
+    fn destructive_actions_wait_for_an_answer() {
+        let mut app = app(&[("home", &["a"]), ("work", &[])]);
+        app.request_action(PendingAction::DeletePage(0));
+        assert_eq!(app.describe_action(PendingAction::DeletePage(0)), "delete page 'home' with 1 todos?");
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.pending_action.is_none());
+        assert_eq!(app.pages.len(), 2);
+
+        app.request_action(PendingAction::DeletePage(0));
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.pending_action.is_some());
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.pending_action.is_none());
+        assert_eq!(app.pages.len(), 1);
+        assert_eq!(app.trash.len(), 1);
+    }
+
+    #[test]
+    fn settings_choose_what_is_confirmed() {
+        let mut app = app(&[("home", &["a", "b"])]);
+        app.request_action(PendingAction::DeleteTodo(0));
+        assert!(app.pending_action.is_none());
+        assert_eq!(names(app.current_todos()), ["b"]);
+
+        app.settings.confirm_delete_todo = true;
+        app.request_action(PendingAction::DeleteTodo(0));
+        assert_eq!(app.describe_action(PendingAction::DeleteTodo(0)), "delete todo 'b'?");
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(names(app.current_todos()), ["b"]);
+
+        app.request_action(PendingAction::PurgeTrash);
+        assert_eq!(app.describe_action(PendingAction::PurgeTrash), "permanently purge all 1 items in the trash?");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.trash.is_empty());
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_early_and_consecutive_matches() {
+        assert_eq!(fuzzy_score("kw", "work"), None);
+        assert_eq!(fuzzy_score("WO", "work"), fuzzy_score("wo", "Work"));
+        assert!(fuzzy_score("wo", "work") > fuzzy_score("wo", "a work"));
+        assert!(fuzzy_score("ho", "home") > fuzzy_score("ho", "h_o"));
+        assert!(fuzzy_score("work", "work") > fuzzy_score("work", "workshop"));
+    }
+
+    #[test]
+    fn picker_lists_the_best_matches_first() {
+        let mut app = app(&[("shopping", &[]), ("homework", &[]), ("home", &[])]);
+        app.page_picker_input = "hom".to_string();
+        assert_eq!(app.picker_matches(), [2, 1]);
+        assert!(app.picker_can_create());
+        assert_eq!(app.picker_len(), 3);
+
+        app.page_picker_input = "home".to_string();
+        assert!(!app.picker_can_create());
+        assert_eq!(app.picker_len(), 2);
+    }
+
+    #[test]
+    fn page_range_grows_around_the_current_tab() {
+        let labels: Vec<String> = ["aaa", "bb", "cccc", "d"].iter().map(|label| label.to_string()).collect();
+        assert_eq!(visible_page_range(&labels, 0, 100), (0, 4));
+        assert_eq!(visible_page_range(&labels, 2, 6), (2, 4));
+        assert_eq!(visible_page_range(&labels, 1, 5), (0, 2));
+        // the current tab is shown even when it doesn't fit
+        assert_eq!(visible_page_range(&labels, 2, 1), (2, 3));
+        assert_eq!(visible_page_range(&[], 0, 10), (0, 0));
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(wrap_text("buy milk and eggs", 8), ["buy milk", "and eggs"]);
+        assert_eq!(wrap_text("buy milk", 20), ["buy milk"]);
+        assert_eq!(wrap_text("", 5), [""]);
+        assert_eq!(wrap_text("ééé ü", 3), ["ééé", "ü"]);
+    }
+
+    #[test]
+    fn splits_words_longer_than_a_line() {
+        assert_eq!(wrap_text("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_text("ab", 0), ["a", "b"]);
+    }
+
+    #[test]
+    fn indents_continuation_lines_under_the_prefix() {
+        let text = wrapped_item("[ ] ", "buy milk and eggs", 12);
+        let lines: Vec<String> = text.lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["[ ] buy milk", "    and eggs"]);
+    }
+
+    #[test]
+    fn temp_files_are_new_and_removed_when_dropped() {
+        let (first, _) = create_temp_file("md").unwrap();
+        let (second, _) = create_temp_file("md").unwrap();
+        assert_ne!(first.0, second.0);
+        assert!(first.0.exists());
+
+        let path = first.0.clone();
+        drop(first);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn editing_a_checklist_completes_recurring_todos_and_stops_removed_timers() {
+        let mut app = app(&[("home", &["water plants", "call mom"])]);
+        app.current_todos_mut()[0].recurrence = Recurrence::parse("daily");
+        app.current_todos_mut()[1].start_timer();
+
+        let (title, items) = markdown::parse_checklist("- [x] water plants\n");
+        app.apply_checklist(0, title, items);
+        let todos = app.current_todos();
+        assert_eq!(names(todos), ["water plants", "water plants"]);
+        assert!(todos[0].completed && todos[0].recurrence.is_none());
+        assert!(!todos[1].completed && todos[1].recurrence.is_some());
+
+        let TrashedItem::Todo { todo, .. } = &app.trash[0].item else { panic!("expected a todo") };
+        assert!(!todo.is_timing());
+    }
+
+    #[test]
+    fn agenda_groups_by_distance_to_the_due_date() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        // a saturday
+        let today = date(14);
+        let group = |due| AgendaGroup::of(due, today).label();
+        assert_eq!(group(Some(date(1))), "overdue");
+        assert_eq!(group(Some(today)), "today");
+        assert_eq!(group(Some(date(15))), "tomorrow");
+        assert_eq!(group(Some(date(20))), "this week");
+        assert_eq!(group(Some(date(21))), "later");
+        assert_eq!(group(None), "no date");
+    }
+
+    #[test]
+    fn agenda_lists_open_todos_from_every_page_soonest_first() {
+        let today = Local::now().date_naive();
+        let mut app = app(&[("home", &["someday", "next month", "done"]), ("work", &["late", "now"])]);
+        app.pages[0].todos[1].due = Some(today + chrono::Duration::days(30));
+        app.pages[0].todos[2].due = Some(today);
+        app.pages[0].todos[2].completed = true;
+        app.pages[1].todos[0].due = Some(today - chrono::Duration::days(2));
+        app.pages[1].todos[1].due = Some(today);
+
+        let order = |app: &App| -> Vec<(usize, usize)> {
+            app.agenda_entries().iter().map(|entry| (entry.page_index, entry.todo_index)).collect()
+        };
+        assert_eq!(order(&app), [(1, 0), (1, 1), (0, 1), (0, 0)]);
+
+        // todos ticked off today stay until tomorrow
+        app.pages[0].todos[2].completed_at = Some(Local::now());
+        assert_eq!(order(&app), [(1, 0), (0, 2), (1, 1), (0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn calendar_stays_put_at_the_ends_of_the_date_range() {
+        let mut app = app(&[("home", &[])]);
+        app.view = View::Calendar;
+        app.calendar_date = NaiveDate::MAX;
+        press(&mut app, KeyCode::Char('L'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.calendar_date, NaiveDate::MAX);
+        assert!(calendar_grid(NaiveDate::MAX).is_none());
+
+        app.calendar_date = NaiveDate::MIN;
+        press(&mut app, KeyCode::Char('H'));
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.calendar_date, NaiveDate::MIN);
+
+        app.calendar_date = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.calendar_date, NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        let monday = NaiveDate::from_ymd_opt(2026, 1, 26).unwrap();
+        assert_eq!(calendar_grid(app.calendar_date), Some((monday, app.calendar_date)));
+    }
+
+    #[test]
+    fn board_moves_cards_between_columns() {
+        let mut app = app(&[("home", &["a", "b"])]);
+        app.view = View::Board;
+        assert_eq!(app.board_cards(), [vec![0, 1], vec![], vec![]]);
+
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.current_todos()[1].status.as_deref(), Some("doing"));
+        assert_eq!(app.board_cards(), [vec![0], vec![1], vec![]]);
+        assert_eq!((app.board_column_index, app.board_row_index), (1, 0));
+
+        press(&mut app, KeyCode::Char('L'));
+        assert!(app.current_todos()[1].completed);
+        assert_eq!(app.current_todos()[1].status, None);
+        press(&mut app, KeyCode::Char('L'));
+        assert_eq!(app.board_cards(), [vec![0], vec![], vec![1]]);
+
+        press(&mut app, KeyCode::Char('H'));
+        assert!(!app.current_todos()[1].completed);
+        assert_eq!(app.board_cards(), [vec![0], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn unknown_statuses_start_in_the_first_column() {
+        let mut app = app(&[("home", &["a", "b"])]);
+        app.current_todos_mut()[0].status = Some("blocked".to_string());
+        // only completed todos go to the last column
+        app.current_todos_mut()[1].status = Some("done".to_string());
+        assert_eq!(app.board_cards(), [vec![0, 1], vec![], vec![]]);
+
+        app.settings.board_columns = vec!["only".to_string()];
+        assert_eq!(app.board_columns(), ["todo", "doing", "done"]);
+    }
+
+    #[test]
+    fn parses_estimates() {
+        assert_eq!(parse_estimate("30"), Some(30));
+        assert_eq!(parse_estimate("~45m"), Some(45));
+        assert_eq!(parse_estimate("1.5h"), Some(90));
+        assert_eq!(parse_estimate("1h30m"), Some(90));
+        assert_eq!(parse_estimate("2d"), Some(16 * 60));
+        assert_eq!(parse_estimate("0"), None);
+        assert_eq!(parse_estimate("soon"), None);
+        assert_eq!(parse_estimate("2h5"), None);
+    }
+
+    #[test]
+    fn huge_estimates_are_invalid() {
+        assert_eq!(parse_estimate("99999999999999999999m"), None);
+        assert_eq!(parse_estimate(&format!("{}h", i64::MAX)), None);
+        let todo = |estimate| Todo { estimate: Some(estimate), ..Todo::new(0, "big".to_string()) };
+        assert_eq!(remaining_estimate(&[todo(i64::MAX), todo(60)]), i64::MAX);
+    }
+
+    #[test]
+    fn splits_an_estimate_with_a_unit_off_the_name() {
+        assert_eq!(split_estimate("write report ~2h"), ("write report".to_string(), Some(120)));
+        assert_eq!(split_estimate("write report ~1h30m "), ("write report".to_string(), Some(90)));
+        assert_eq!(split_estimate("call back ~10"), ("call back ~10".to_string(), None));
+        assert_eq!(split_estimate("approx ~ten"), ("approx ~ten".to_string(), None));
+    }
+
+    #[test]
+    fn formats_estimates() {
+        assert_eq!(format_estimate(45), "45m");
+        assert_eq!(format_estimate(120), "2h");
+        assert_eq!(format_estimate(95), "1h35m");
+    }
+
+    #[test]
+    fn completing_a_blocked_card_asks_first() {
+        let mut app = app(&[("home", &["a", "b"])]);
+        let blocker = app.current_todos()[0].id;
+        app.current_todos_mut()[1].depends_on = vec![blocker];
+        app.current_todos_mut()[1].status = Some("doing".to_string());
+        app.view = View::Board;
+        app.board_column_index = 1;
+
+        press(&mut app, KeyCode::Char('L'));
+        assert!(!app.current_todos()[1].completed);
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.current_todos()[1].completed);
+    }
+
+    #[test]
+    fn linking_only_lets_navigation_through() {
+        let mut app = app(&[("home", &["a", "b"]), ("work", &[])]);
+        app.linking_todo = Some(app.current_todos()[0].id);
+        // keys that aren't handled here go on to the todo list
+        let mut handled = |code| app.process_input_event(KeyEvent::from(code));
+        assert!(!handled(KeyCode::Char('j')));
+        assert!(!handled(KeyCode::Char('p')));
+        assert!(!handled(KeyCode::Char('2')));
+        assert!(handled(KeyCode::Char('q')));
+        assert!(handled(KeyCode::Char('1')));
+        assert!(handled(KeyCode::Char('3')));
+        assert!(app.linking_todo.is_some() && !app.should_quit);
+    }
+
+    #[test]
+    fn assign_ids_fills_in_ids_missing_from_older_files() {
+        let json = r#"{"pages": [
+            {"name": "home", "todos": [{"name": "a", "completed": false, "id": 1000000}, {"name": "b", "completed": false}]},
+            {"name": "work", "todos": []}
+        ]}"#;
+        let mut data: AppData = serde_json::from_str(json).unwrap();
+        assign_ids(&mut data);
+
+        let mut ids = vec![data.pages[0].id, data.pages[0].todos[1].id, data.pages[1].id];
+        assert_eq!(data.pages[0].todos[0].id, 1000000);
+        assert!(ids.iter().all(|&id| id > 1000000));
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert!(data.next_id > ids[2]);
+    }
+
+    #[test]
+    fn new_todos_take_ids_from_the_data() {
+        let mut app = app(&[("home", &["water plants"])]);
+        assert_eq!(app.next_id, 3);
+        app.current_todos_mut()[0].recurrence = Recurrence::parse("daily");
+        app.toggle_todo(0);
+        assert_eq!(app.current_todos()[1].id, 3);
+        assert_eq!(app.next_id, 4);
+
+        // a counter of 0 in older files still never hands out 0
+        let mut next_id = 0;
+        assert_eq!((take_id(&mut next_id), take_id(&mut next_id)), (1, 2));
+    }
+
+    #[test]
+    fn display_order_follows_the_sort_mode() {
+        let mut app = app(&[("home", &["Cherry", "apple", "banana"])]);
+        app.current_todos_mut()[0].priority = Some(Priority::Low);
+        app.current_todos_mut()[1].due = NaiveDate::from_ymd_opt(2026, 3, 14);
+        app.current_todos_mut()[2].priority = Some(Priority::High);
+        app.current_todos_mut()[0].completed = true;
+
+        let mut order = |sort: SortMode| {
+            app.pages[0].sort = sort;
+            app.display_order()
+        };
+        assert_eq!(order(SortMode::Manual), [0, 1, 2]);
+        assert_eq!(order(SortMode::Alpha), [1, 2, 0]);
+        assert_eq!(order(SortMode::OpenFirst), [1, 2, 0]);
+        assert_eq!(order(SortMode::Due), [1, 0, 2]);
+        assert_eq!(order(SortMode::Priority), [2, 0, 1]);
+    }
+
+    #[test]
+    fn moving_a_todo_steps_over_hidden_ones() {
+        let mut app = app(&[("home", &["a", "b", "c", "d"])]);
+        app.current_todos_mut()[1].completed = true;
+        app.settings.hide_completed = true;
+
+        assert!(app.move_todo(true));
+        assert_eq!(names(app.current_todos()), ["c", "b", "a", "d"]);
+        assert_eq!(app.selected_todo_index, 2);
+        assert!(app.move_todo(false));
+        assert_eq!(names(app.current_todos()), ["a", "b", "c", "d"]);
+        assert_eq!(app.selected_todo_index, 0);
+
+        // wraps around like the selection does
+        assert!(app.move_todo(false));
+        assert_eq!(names(app.current_todos()), ["d", "b", "c", "a"]);
+        assert_eq!(app.selected_todo_index, 3);
+    }
+
+    #[test]
+    fn completed_todos_sink_below_open_ones() {
+        let mut app = app(&[("home", &["a", "b", "c"])]);
+        app.settings.sink_completed = true;
+        app.toggle_todo(0);
+        assert_eq!(names(app.current_todos()), ["b", "c", "a"]);
+        assert_eq!(app.selected_todo_index, 0);
+
+        app.toggle_todo(0);
+        assert_eq!(names(app.current_todos()), ["c", "a", "b"]);
+
+        // reopened todos go right after the open ones
+        app.toggle_todo(2);
+        assert_eq!(names(app.current_todos()), ["c", "b", "a"]);
+        assert_eq!(app.selected_todo_index, 1);
+    }
+
+    #[test]
+    fn hidden_todos_never_stay_selected() {
+        let mut app = app(&[("home", &["a", "b", "c", "d"])]);
+        app.current_todos_mut()[1].completed = true;
+        app.current_todos_mut()[3].completed = true;
+        app.settings.hide_completed = true;
+        assert_eq!(app.display_order(), [0, 2]);
+
+        app.selected_todo_index = 1;
+        assert!(!app.has_selection());
+        app.clamp_selection();
+        assert_eq!(app.selected_todo_index, 2);
+
+        // nothing is shown after the last todo, so the selection moves back
+        app.selected_todo_index = 3;
+        app.clamp_selection();
+        assert_eq!(app.selected_todo_index, 2);
+
+        app.settings.hide_completed = false;
+        app.selected_todo_index = 3;
+        app.clamp_selection();
+        assert_eq!(app.selected_todo_index, 3);
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn prompts_keep_the_todo_they_were_opened_on() {
+        let mut app = app(&[("home", &["a", "b"])]);
+        app.current_todos_mut()[0].completed = true;
+        app.settings.hide_completed = true;
+        app.start_filtering();
+        type_text(&mut app, "done");
+        press(&mut app, KeyCode::Enter);
+
+        // the run loop clamps after every key
+        press(&mut app, KeyCode::Char('r'));
+        app.clamp_selection();
+        type_text(&mut app, "2");
+        app.clamp_selection();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(names(app.current_todos()), ["a2", "b"]);
+        app.clamp_selection();
+        assert_eq!(app.selected_todo_index, 1);
+    }
+
+    #[test]
+    fn saved_views_are_tabs_after_the_pages() {
+        let mut app = app(&[("home", &["water plants", "call mom"]), ("work", &["plants report"])]);
+        app.start_filtering();
+        type_text(&mut app, "plants");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.view == View::Filter);
+        assert_eq!(app.filter_entries(), [(0, 0), (1, 0)]);
+
+        press(&mut app, KeyCode::Char('s'));
+        type_text(&mut app, "garden");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!((app.views[0].name.as_str(), app.views[0].filter.as_str()), ("garden", "plants"));
+        assert_eq!(app.current_tab(), 2);
+
+        app.select_tab(1);
+        assert!(app.view == View::Todos);
+        app.step_tab(true);
+        assert!(app.view == View::Filter);
+        assert_eq!(app.current_view, Some(0));
+        assert_eq!(app.filter_query, "plants");
+        app.step_tab(true);
+        assert_eq!(app.current_tab(), 0);
+    }
+
+    #[test]
+    fn browsing_the_filter_view_leaves_the_list_alone() {
+        let mut app = app(&[("home", &["a"]), ("work", &["plants report"])]);
+        app.start_filtering();
+        type_text(&mut app, "plants");
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.current_page_index, 0);
+
+        press(&mut app, KeyCode::Char('o'));
+        assert!(app.view == View::Todos);
+        assert_eq!((app.current_page_index, app.selected_todo_index), (1, 0));
+    }
+
+    #[test]
+    fn saving_under_the_same_name_updates_the_view() {
+        let mut app = app(&[("home", &["water plants", "call mom"])]);
+        app.views.push(SavedView { name: "garden".to_string(), filter: "plants".to_string() });
+        app.select_tab(1);
+
+        app.start_filtering();
+        app.filter_input = "call".to_string();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.filter_entries(), [(0, 1)]);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.view_name_input, "garden");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.views.len(), 1);
+        assert_eq!(app.views[0].filter, "call");
+
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.views.is_empty());
+        assert_eq!(app.current_view, None);
+    }
+}
@@ -0,0 +1,415 @@
+use crate::Page;
+
+pub struct ChecklistItem {
+    pub name: String,
+    pub completed: bool,
+    pub notes: String,
+    // index of the item this one is nested under, if any
+    pub parent: Option<usize>,
+}
+
+pub fn page_to_checklist(page: &Page) -> String {
+    let mut out = format!("# {}\n\n", page.name);
+    for todo in &page.todos {
+        let checkbox = if todo.completed { "[x]" } else { "[ ]" };
+        out.push_str(&format!("- {} {}\n", checkbox, todo.name));
+        for line in todo.notes.lines() {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("  {}\n", escape_note(line)));
+            }
+        }
+    }
+    out
+}
+
+// returns the `# heading` if there is one, and the checklist items in order.
+// lines under an item become its notes, unless they have a checkbox or are an unindented bullet
+pub fn parse_checklist(text: &str) -> (Option<String>, Vec<ChecklistItem>) {
+    let mut title = None;
+    let mut items: Vec<ChecklistItem> = vec![];
+    let mut pending_blank_lines = 0;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.trim().is_empty() {
+            pending_blank_lines += 1;
+            continue;
+        }
+
+        let is_note = !items.is_empty() && !has_checkbox(trimmed) && (indent >= 2 || parse_item(trimmed).is_none());
+        if is_note {
+            let item = items.last_mut().unwrap();
+            if !item.notes.is_empty() {
+                for _ in 0..pending_blank_lines {
+                    item.notes.push('\n');
+                }
+                item.notes.push('\n');
+            }
+            let note = line.strip_prefix("  ").or_else(|| line.strip_prefix('\t')).unwrap_or(trimmed);
+            item.notes.push_str(&unescape_note(note));
+            pending_blank_lines = 0;
+            continue;
+        }
+        pending_blank_lines = 0;
+
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            if title.is_none() && items.is_empty() {
+                title = Some(heading.trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some((name, completed)) = parse_item(trimmed) {
+            items.push(ChecklistItem {
+                name,
+                completed,
+                notes: String::new(),
+                parent: None,
+            });
+        }
+    }
+
+    (title, items)
+}
+
+// notes can hold bulleted lists, but only a line with a checkbox is an item
+fn has_checkbox(line: &str) -> bool {
+    line.strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .is_some_and(|rest| rest.starts_with("[ ]") || rest.starts_with("[x]") || rest.starts_with("[X]"))
+}
+
+// a note line that would read back as an item is written as `\- [ ] ...`. lines already
+// starting with backslashes get one more so reading them back is exact
+fn escape_note(line: &str) -> String {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    if has_checkbox(trimmed.trim_start_matches('\\')) {
+        format!("{}\\{}", &line[..line.len() - trimmed.len()], trimmed)
+    } else {
+        line.to_string()
+    }
+}
+
+fn unescape_note(line: &str) -> String {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    match trimmed.strip_prefix('\\') {
+        Some(rest) if has_checkbox(rest.trim_start_matches('\\')) => {
+            format!("{}{}", &line[..line.len() - trimmed.len()], rest)
+        }
+        _ => line.to_string(),
+    }
+}
+
+// accepts `- [ ] name`, `- [x] name` and plain `- name` bullets
+fn parse_item(line: &str) -> Option<(String, bool)> {
+    let rest = line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))?;
+    let (completed, name) = if let Some(name) = rest.strip_prefix("[ ]") {
+        (false, name)
+    } else if let Some(name) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+        (true, name)
+    } else {
+        (false, rest)
+    };
+
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name.to_string(), completed))
+    }
+}
+
+// a `## page` section of a Markdown document with its items in order
+pub struct Section {
+    pub page: String,
+    pub items: Vec<ChecklistItem>,
+}
+
+// every page as a `## name` section. a todo another todo on the same page depends on is
+// nested under the first one that does, so blocking todos read as subtasks
+pub fn pages_to_markdown(pages: &[&Page]) -> String {
+    let mut out = String::new();
+    for page in pages {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("## {}\n\n", page.name));
+
+        let mut parents: Vec<Option<usize>> = vec![None; page.todos.len()];
+        for (index, todo) in page.todos.iter().enumerate() {
+            for id in &todo.depends_on {
+                if let Some(child) = page.todos.iter().position(|other| other.id == *id) {
+                    if parents[child].is_none() && child != index {
+                        parents[child] = Some(index);
+                    }
+                }
+            }
+        }
+
+        let mut written = vec![false; page.todos.len()];
+        for index in 0..page.todos.len() {
+            if parents[index].is_none() {
+                write_item(&mut out, page, &parents, &mut written, index, 0);
+            }
+        }
+        // todos caught in a dependency cycle have no root to hang from
+        for index in 0..page.todos.len() {
+            if !written[index] {
+                write_item(&mut out, page, &parents, &mut written, index, 0);
+            }
+        }
+    }
+    out
+}
+
+fn write_item(out: &mut String, page: &Page, parents: &[Option<usize>], written: &mut [bool], index: usize, depth: usize) {
+    if written[index] {
+        return;
+    }
+    written[index] = true;
+
+    let todo = &page.todos[index];
+    let indent = "  ".repeat(depth);
+    let checkbox = if todo.completed { "[x]" } else { "[ ]" };
+    out.push_str(&format!("{}- {} {}\n", indent, checkbox, todo.name));
+    for line in todo.notes.lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            out.push_str(&format!("{}  {}\n", indent, escape_note(line)));
+        }
+    }
+    for child in 0..page.todos.len() {
+        if parents[child] == Some(index) {
+            write_item(out, page, parents, written, child, depth + 1);
+        }
+    }
+}
+
+// the reverse of `pages_to_markdown`. items before the first `## heading` go to `default_page`,
+// a nested item with a checkbox records the item it sits under and other indented lines,
+// bullets included, become notes
+pub fn parse_sections(text: &str, default_page: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = vec![];
+    // indent of each open item and its index, outermost first
+    let mut open: Vec<(usize, usize)> = vec![];
+    let mut pending_blank_lines = 0;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.trim().is_empty() {
+            pending_blank_lines += 1;
+            continue;
+        }
+
+        // indented headings belong to notes
+        if let Some(heading) = trimmed.strip_prefix("## ").filter(|_| indent == 0) {
+            sections.push(Section {
+                page: heading.trim().to_string(),
+                items: vec![],
+            });
+            open.clear();
+            pending_blank_lines = 0;
+            continue;
+        }
+        if trimmed.starts_with('#') && indent == 0 {
+            pending_blank_lines = 0;
+            continue;
+        }
+
+        while open.last().is_some_and(|(open_indent, _)| *open_indent >= indent) {
+            open.pop();
+        }
+        let item = parse_item(trimmed).filter(|_| open.is_empty() || has_checkbox(trimmed));
+
+        match item {
+            Some((name, completed)) => {
+                if sections.is_empty() {
+                    sections.push(Section {
+                        page: default_page.to_string(),
+                        items: vec![],
+                    });
+                }
+                let items = &mut sections.last_mut().unwrap().items;
+                items.push(ChecklistItem {
+                    name,
+                    completed,
+                    notes: String::new(),
+                    parent: open.last().map(|(_, index)| *index),
+                });
+                open.push((indent, items.len() - 1));
+            }
+            None => {
+                // text that isn't indented under an item has nowhere to go
+                let Some((item_indent, index)) = open.last().copied() else {
+                    pending_blank_lines = 0;
+                    continue;
+                };
+                let item = &mut sections.last_mut().unwrap().items[index];
+                if !item.notes.is_empty() {
+                    for _ in 0..pending_blank_lines {
+                        item.notes.push('\n');
+                    }
+                    item.notes.push('\n');
+                }
+                let note_indent = (item_indent + 2).min(indent);
+                item.notes.push_str(&unescape_note(&line[note_indent..]));
+            }
+        }
+        pending_blank_lines = 0;
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Todo;
+
+    fn page(name: &str, todos: &[(&str, bool, &str)]) -> Page {
+        let mut page = Page::new(1, name.to_string());
+        for (id, (name, completed, notes)) in (2..).zip(todos) {
+            let mut todo = Todo::new(id, name.to_string());
+            todo.completed = *completed;
+            todo.notes = notes.to_string();
+            page.todos.push(todo);
+        }
+        page
+    }
+
+    fn names(items: &[ChecklistItem]) -> Vec<(&str, bool, &str)> {
+        items.iter().map(|item| (item.name.as_str(), item.completed, item.notes.as_str())).collect()
+    }
+
+    #[test]
+    fn parses_a_checklist() {
+        let text = "# groceries\n\n- [ ] milk\n  two liters\n\n  semi-skimmed\n- [x] bread\n* [X] eggs\n- butter\n";
+        let (title, items) = parse_checklist(text);
+        assert_eq!(title.as_deref(), Some("groceries"));
+        assert_eq!(
+            names(&items),
+            [("milk", false, "two liters\n\nsemi-skimmed"), ("bread", true, ""), ("eggs", true, ""), ("butter", false, "")]
+        );
+    }
+
+    #[test]
+    fn bullets_in_notes_stay_notes() {
+        let text = "- [ ] release\n  - changelog\n  * tag\n  - [ ] nested step\n";
+        let (_, items) = parse_checklist(text);
+        assert_eq!(names(&items), [("release", false, "- changelog\n* tag"), ("nested step", false, "")]);
+    }
+
+    #[test]
+    fn unindented_lines_under_an_item_are_notes() {
+        let text = "# list\n\nintro\n- [ ] milk\ntwo liters\n semi-skimmed\n\tfrom the farm\n- butter\n";
+        let (_, items) = parse_checklist(text);
+        assert_eq!(names(&items), [("milk", false, "two liters\nsemi-skimmed\nfrom the farm"), ("butter", false, "")]);
+    }
+
+    #[test]
+    fn checklist_round_trips() {
+        let page = page(
+            "release",
+            &[
+                ("ship", false, "links:\n- https://example.com\n\n- [ ] not an item\n\\- [x] escaped\n  indented"),
+                ("announce", true, ""),
+            ],
+        );
+        let text = page_to_checklist(&page);
+        assert!(text.contains("\n  \\- [ ] not an item\n"));
+
+        let (title, items) = parse_checklist(&text);
+        assert_eq!(title.as_deref(), Some("release"));
+        let expected: Vec<(&str, bool, &str)> =
+            page.todos.iter().map(|todo| (todo.name.as_str(), todo.completed, todo.notes.as_str())).collect();
+        assert_eq!(names(&items), expected);
+    }
+
+    #[test]
+    fn unicode_indents_do_not_panic() {
+        let (_, items) = parse_checklist("- [ ] item\n \u{a0}note\n\u{3000}- other\n");
+        assert_eq!(items.len(), 1);
+    }
+
+    fn section_items(section: &Section) -> Vec<(&str, bool, Option<usize>)> {
+        section.items.iter().map(|item| (item.name.as_str(), item.completed, item.parent)).collect()
+    }
+
+    #[test]
+    fn parses_sections_with_nested_items() {
+        let text = "# export\n\n- [ ] loose\n\n## Launch\n\n- [ ] ship\n  some notes\n  - [x] changelog\n  - [ ] tag\n    - [ ] run ci\n- [x] announce\n\n## work\n* plain\n";
+        let sections = parse_sections(text, "inbox");
+        let pages: Vec<&str> = sections.iter().map(|section| section.page.as_str()).collect();
+        assert_eq!(pages, ["inbox", "Launch", "work"]);
+        assert_eq!(section_items(&sections[0]), [("loose", false, None)]);
+        assert_eq!(
+            section_items(&sections[1]),
+            [
+                ("ship", false, None),
+                ("changelog", true, Some(0)),
+                ("tag", false, Some(0)),
+                ("run ci", false, Some(2)),
+                ("announce", true, None),
+            ]
+        );
+        assert_eq!(sections[1].items[0].notes, "some notes");
+        assert_eq!(section_items(&sections[2]), [("plain", false, None)]);
+    }
+
+    #[test]
+    fn indented_headings_and_bullets_stay_in_notes() {
+        let text = "## page\n- [ ] item\n  ## Context\n  - a bullet\n  * another\n  # title\n";
+        let sections = parse_sections(text, "inbox");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(section_items(&sections[0]), [("item", false, None)]);
+        assert_eq!(sections[0].items[0].notes, "## Context\n- a bullet\n* another\n# title");
+    }
+
+    #[test]
+    fn unicode_indents_in_sections_do_not_panic() {
+        let text = "- [ ] item\n \u{a0}note\n  \u{3000}deeper\n\u{3000}not indented\n";
+        let sections = parse_sections(text, "inbox");
+        assert_eq!(sections[0].items.len(), 1);
+        assert_eq!(sections[0].items[0].notes, "\u{a0}note\n\u{3000}deeper");
+    }
+
+    #[test]
+    fn dependencies_are_nested_and_read_back() {
+        let mut page = page(
+            "launch",
+            &[("ship", false, "- [ ] not a subtask\n- a bullet"), ("changelog", true, ""), ("tag", false, "")],
+        );
+        let (changelog, tag) = (page.todos[1].id, page.todos[2].id);
+        page.todos[0].depends_on = vec![changelog, tag];
+
+        let text = pages_to_markdown(&[&page]);
+        assert_eq!(
+            text,
+            "## launch\n\n- [ ] ship\n  \\- [ ] not a subtask\n  - a bullet\n  - [x] changelog\n  - [ ] tag\n"
+        );
+
+        let sections = parse_sections(&text, "inbox");
+        assert_eq!(sections[0].page, "launch");
+        assert_eq!(section_items(&sections[0]), [("ship", false, None), ("changelog", true, Some(0)), ("tag", false, Some(0))]);
+        assert_eq!(sections[0].items[0].notes, page.todos[0].notes);
+    }
+
+    #[test]
+    fn dependency_cycles_are_still_written() {
+        let mut page = page("loop", &[("a", false, ""), ("b", false, "")]);
+        let (a, b) = (page.todos[0].id, page.todos[1].id);
+        page.todos[0].depends_on = vec![b];
+        page.todos[1].depends_on = vec![a];
+
+        let text = pages_to_markdown(&[&page]);
+        assert_eq!(text.matches("- [ ] a").count(), 1);
+        assert_eq!(text.matches("- [ ] b").count(), 1);
+    }
+}
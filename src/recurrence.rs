@@ -0,0 +1,194 @@
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::dates::{add_days, parse_offset, parse_weekday, weekday_name};
+
+// stored in the data file as the same text the user types, e.g. "weekly mon,thu"
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    Daily,
+    Weekdays,
+    Weekly(Vec<Weekday>),
+    Monthly(u32),
+    // counted from the day the todo is completed rather than its due date
+    AfterCompletion(i64),
+}
+
+impl Recurrence {
+    // accepts `daily`, `weekdays`, `weekly mon,thu`, `monthly 1` and `every 3d`
+    pub fn parse(input: &str) -> Option<Recurrence> {
+        let input = input.trim().to_lowercase();
+        let (kind, rest) = match input.split_once(' ') {
+            Some((kind, rest)) => (kind, rest.trim()),
+            None => (input.as_str(), ""),
+        };
+
+        match (kind, rest) {
+            ("daily", "") => Some(Recurrence::Daily),
+            ("weekdays", "") => Some(Recurrence::Weekdays),
+            ("weekly", days) => {
+                let days = days
+                    .split([',', ' '])
+                    .filter(|day| !day.is_empty())
+                    .map(parse_weekday)
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Recurrence::Weekly(days))
+            }
+            ("monthly", "") => Some(Recurrence::Monthly(0)),
+            ("monthly", day) => {
+                let day = day.trim_end_matches(|c: char| c.is_ascii_alphabetic()).parse().ok()?;
+                (1..=31).contains(&day).then_some(Recurrence::Monthly(day))
+            }
+            ("every", offset) => {
+                let days = parse_offset(offset.trim_end_matches("days").trim_end_matches("day"))?;
+                (days > 0).then_some(Recurrence::AfterCompletion(days))
+            }
+            _ => None,
+        }
+    }
+
+    // the due date of the occurrence after one due on `due` (or undated) completed `today`.
+    // always lands after today so overdue series skip ahead instead of piling up.
+    // `None` when the next date is past the last one chrono can represent
+    pub fn next_due(&self, due: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate> {
+        if let Recurrence::AfterCompletion(days) = self {
+            return add_days(today, *days);
+        }
+
+        let base = due.unwrap_or(today);
+        let mut next = self.advance(base, base)?;
+        while next <= today {
+            next = self.advance(next, base)?;
+        }
+        Some(next)
+    }
+
+    fn advance(&self, date: NaiveDate, anchor: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => date.succ_opt(),
+            Recurrence::Weekdays => {
+                let mut next = date.succ_opt()?;
+                while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+                    next = next.succ_opt()?;
+                }
+                Some(next)
+            }
+            Recurrence::Weekly(days) if days.is_empty() => add_days(date, 7),
+            Recurrence::Weekly(days) => {
+                let mut next = date.succ_opt()?;
+                while !days.contains(&next.weekday()) {
+                    next = next.succ_opt()?;
+                }
+                Some(next)
+            }
+            Recurrence::Monthly(day) => {
+                let day = if *day == 0 { anchor.day() } else { *day };
+                // clamps to the last day of short months
+                let day_in_month = |month_start: NaiveDate| {
+                    let last_day = month_start.checked_add_months(Months::new(1))?.pred_opt()?.day();
+                    month_start.with_day(day.min(last_day))
+                };
+
+                let month_start = date.with_day(1)?;
+                let this_month = day_in_month(month_start)?;
+                if this_month > date {
+                    Some(this_month)
+                } else {
+                    day_in_month(month_start.checked_add_months(Months::new(1))?)
+                }
+            }
+            Recurrence::AfterCompletion(days) => add_days(date, *days),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekdays => write!(f, "weekdays"),
+            Recurrence::Weekly(days) if days.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly(days) => {
+                let days: Vec<&str> = days.iter().map(|day| weekday_name(*day)).collect();
+                write!(f, "weekly {}", days.join(","))
+            }
+            Recurrence::Monthly(0) => write!(f, "monthly"),
+            Recurrence::Monthly(day) => write!(f, "monthly {}", day),
+            Recurrence::AfterCompletion(days) => write!(f, "every {}d", days),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Recurrence::parse(&value).ok_or_else(|| format!("invalid recurrence '{}'", value))
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(value: Recurrence) -> Self {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays_the_same_text() {
+        for input in ["daily", "weekdays", "weekly", "weekly mon,thu", "monthly", "monthly 15", "every 3d"] {
+            assert_eq!(Recurrence::parse(input).unwrap().to_string(), input);
+        }
+        assert_eq!(Recurrence::parse("every 2w"), Some(Recurrence::AfterCompletion(14)));
+        assert_eq!(Recurrence::parse("monthly 1st"), Some(Recurrence::Monthly(1)));
+        assert_eq!(Recurrence::parse("monthly 32"), None);
+        assert_eq!(Recurrence::parse("every 0d"), None);
+        assert_eq!(Recurrence::parse("weekly someday"), None);
+        assert_eq!(Recurrence::parse("hourly"), None);
+    }
+
+    #[test]
+    fn next_due_follows_the_schedule() {
+        // a saturday
+        let today = date("2026-03-14");
+        let next = |input: &str, due: Option<&str>| Recurrence::parse(input).unwrap().next_due(due.map(date), today);
+        assert_eq!(next("daily", Some("2026-03-14")), Some(date("2026-03-15")));
+        assert_eq!(next("weekdays", Some("2026-03-13")), Some(date("2026-03-16")));
+        assert_eq!(next("weekly mon,thu", Some("2026-03-12")), Some(date("2026-03-16")));
+        assert_eq!(next("every 3d", Some("2026-01-01")), Some(date("2026-03-17")));
+        assert_eq!(next("weekly", None), Some(date("2026-03-21")));
+    }
+
+    #[test]
+    fn overdue_series_skip_past_today() {
+        let today = date("2026-03-14");
+        let daily = Recurrence::Daily;
+        assert_eq!(daily.next_due(Some(date("2026-01-01")), today), Some(date("2026-03-15")));
+    }
+
+    #[test]
+    fn monthly_clamps_to_short_months() {
+        let today = date("2026-01-31");
+        let monthly = Recurrence::parse("monthly 31").unwrap();
+        assert_eq!(monthly.next_due(Some(today), today), Some(date("2026-02-28")));
+        assert_eq!(monthly.next_due(Some(date("2026-02-28")), date("2026-02-28")), Some(date("2026-03-31")));
+    }
+
+    #[test]
+    fn huge_intervals_end_the_series() {
+        let today = date("2026-03-14");
+        let every = Recurrence::parse("every 99999999d").unwrap();
+        assert_eq!(every.next_due(Some(today), today), None);
+        assert_eq!(Recurrence::Daily.next_due(Some(NaiveDate::MAX), NaiveDate::MAX), None);
+        assert_eq!(Recurrence::Monthly(0).next_due(Some(NaiveDate::MAX), NaiveDate::MAX), None);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::{Priority, Todo};
+
+// a todo.txt line mapped onto a todo, with the page named by its first `+project`
+pub struct Task {
+    pub project: Option<String>,
+    pub todo: Todo,
+}
+
+// `x 2026-03-14 2026-03-01 (A) call mom +family @phone due:2026-03-20`.
+// `@context`s become `#context` tags and other `key:value` pairs are kept in the name
+pub fn parse_line(line: &str) -> Option<Task> {
+    let mut words = line.split_whitespace().peekable();
+    words.peek()?;
+
+    let completed = words.next_if_eq(&"x").is_some();
+    let mut priority = words.next_if(|word| parse_priority(word).is_some()).and_then(parse_priority);
+
+    // a completed task has its completion date first, then the creation date
+    let first_date = words.next_if(|word| parse_date(word).is_some()).and_then(parse_date);
+    let second_date = words.next_if(|word| parse_date(word).is_some()).and_then(parse_date);
+    let (completed_at, created_at) = match (completed, first_date, second_date) {
+        (true, Some(done), created) => (Some(done), created),
+        (_, first, _) => (None, first),
+    };
+    if priority.is_none() {
+        priority = words.next_if(|word| parse_priority(word).is_some()).and_then(parse_priority);
+    }
+
+    let mut project = None;
+    let mut due = None;
+    let mut name_words = vec![];
+    for word in words {
+        if let Some(name) = word.strip_prefix('+').filter(|name| !name.is_empty()) {
+            if project.is_none() {
+                project = Some(name.to_string());
+                continue;
+            }
+        } else if let Some(context) = word.strip_prefix('@').filter(|context| !context.is_empty()) {
+            name_words.push(format!("#{}", context));
+            continue;
+        } else if let Some(date) = word.strip_prefix("due:").and_then(parse_date) {
+            due = Some(date);
+            continue;
+        } else if let Some(letter) = word.strip_prefix("pri:") {
+            // completed tasks often keep their priority as `pri:A`
+            if let Some(value) = parse_priority(&format!("({})", letter)) {
+                priority = Some(value);
+                continue;
+            }
+        }
+        name_words.push(word.to_string());
+    }
+
+    let name = name_words.join(" ");
+    if name.is_empty() {
+        return None;
+    }
+
+    // the id is given when the task is merged into a page
+    let mut todo = Todo::new(0, name);
+    todo.completed = completed;
+    todo.priority = priority;
+    todo.due = due;
+    // dates the line leaves out stay unknown rather than becoming today
+    todo.created_at = created_at.and_then(start_of_day);
+    todo.completed_at = completed_at.and_then(start_of_day);
+    Some(Task { project, todo })
+}
+
+// the reverse of `parse_line`: `#tags` are written as `@contexts` and the page as the first `+project`.
+// completed todos keep their priority as `pri:A` as the format suggests
+pub fn format_line(page: &str, todo: &Todo) -> String {
+    let mut parts = vec![];
+    if todo.completed {
+        parts.push("x".to_string());
+    }
+    if let (false, Some(priority)) = (todo.completed, todo.priority) {
+        parts.push(format!("({})", priority_letter(priority)));
+    }
+    // the creation date can only follow a completion date on completed tasks
+    let completed_at = todo.completed_at.filter(|_| todo.completed);
+    if let Some(completed_at) = completed_at {
+        parts.push(completed_at.format("%Y-%m-%d").to_string());
+    }
+    if let Some(created_at) = todo.created_at.filter(|_| !todo.completed || completed_at.is_some()) {
+        parts.push(created_at.format("%Y-%m-%d").to_string());
+    }
+
+    // ahead of the name so any `+word` in the name isn't read back as the page
+    let project = project_name(page);
+    if !project.is_empty() {
+        parts.push(format!("+{}", project));
+    }
+    for word in todo.name.split_whitespace() {
+        match word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            Some(tag) => parts.push(format!("@{}", tag)),
+            None => parts.push(word.to_string()),
+        }
+    }
+
+    if let Some(due) = todo.due {
+        parts.push(format!("due:{}", due.format("%Y-%m-%d")));
+    }
+    if let (true, Some(priority)) = (todo.completed, todo.priority) {
+        parts.push(format!("pri:{}", priority_letter(priority)));
+    }
+    parts.join(" ")
+}
+
+// the `+project` a page is written as. projects can't hold spaces, so `work stuff` becomes
+// `work-stuff`
+pub fn project_name(page: &str) -> String {
+    page.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+// (A) is high, (B) medium and anything lower is low
+fn parse_priority(word: &str) -> Option<Priority> {
+    let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+    match letter {
+        "A" => Some(Priority::High),
+        "B" => Some(Priority::Medium),
+        letter if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()
+}
+
+fn start_of_day(date: NaiveDate) -> Option<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_an_open_task() {
+        let task = parse_line("(A) 2026-03-01 call mom +family @phone due:2026-03-20").unwrap();
+        assert_eq!(task.project.as_deref(), Some("family"));
+        assert_eq!(task.todo.name, "call mom #phone");
+        assert!(!task.todo.completed);
+        assert_eq!(task.todo.priority, Some(Priority::High));
+        assert_eq!(task.todo.due, Some(date(2026, 3, 20)));
+        assert_eq!(task.todo.created_at.map(|created| created.date_naive()), Some(date(2026, 3, 1)));
+        assert_eq!(task.todo.completed_at, None);
+    }
+
+    #[test]
+    fn parses_a_completed_task() {
+        let task = parse_line("x 2026-03-14 2026-03-01 file taxes pri:B").unwrap();
+        assert_eq!(task.project, None);
+        assert_eq!(task.todo.name, "file taxes");
+        assert!(task.todo.completed);
+        assert_eq!(task.todo.priority, Some(Priority::Medium));
+        assert_eq!(task.todo.completed_at.map(|done| done.date_naive()), Some(date(2026, 3, 14)));
+        assert_eq!(task.todo.created_at.map(|created| created.date_naive()), Some(date(2026, 3, 1)));
+    }
+
+    #[test]
+    fn missing_dates_stay_unknown() {
+        let task = parse_line("x (C) water plants +home").unwrap();
+        assert_eq!(task.todo.priority, Some(Priority::Low));
+        assert_eq!(task.todo.created_at, None);
+        assert_eq!(task.todo.completed_at, None);
+    }
+
+    #[test]
+    fn skips_empty_lines() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("   ").is_none());
+        assert!(parse_line("x 2026-03-14 +home").is_none());
+    }
+
+    #[test]
+    fn formats_the_reverse_of_parsing() {
+        let line = "(A) 2026-03-01 +family call mom @phone due:2026-03-20";
+        let task = parse_line(line).unwrap();
+        assert_eq!(format_line("family", &task.todo), line);
+
+        let line = "x 2026-03-14 2026-03-01 +home file taxes pri:B";
+        let task = parse_line(line).unwrap();
+        assert_eq!(format_line("home", &task.todo), line);
+    }
+
+    #[test]
+    fn pages_with_spaces_become_hyphenated_projects() {
+        assert_eq!(project_name("work  stuff"), "work-stuff");
+        let mut todo = Todo::new(0, "plan +q3 review".to_string());
+        todo.created_at = None;
+        let line = format_line("work stuff", &todo);
+        assert_eq!(line, "+work-stuff plan +q3 review");
+
+        let task = parse_line(&line).unwrap();
+        assert_eq!(task.project.as_deref(), Some("work-stuff"));
+        assert_eq!(task.todo.name, "plan +q3 review");
+    }
+}